@@ -0,0 +1,262 @@
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+
+use crate::error::{EsianolopError, EsianolopErrorKind};
+use crate::lexer::{self, Side, Span, Token, TokenKind};
+use crate::value::OverflowPolicy;
+
+// L'arbre du programme (AST) : le code est découpé et analysé une seule fois, puis executé autant de fois qu'on veut
+// (boucles, appels de fonction...) sans jamais relire le texte.
+
+// Les instructions de base, qui agissent directement sur le stack
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Add, Sub, Mul, Div, Pow,
+    Mod, Rem, FDiv, DivMod,
+    Eq, Ne, Lt, Le, Gt, Ge,
+    And, Or,
+    Dup, DpL, DpR,
+    Sqr, Log, Exp, Sin, Cos, Tan,
+    Floor, Ceil, Round, Not,
+    Gcd, Lcm, Abs, Min, Max, Fact, Choose, IsPrime, NextPrime, ModPow,
+    BAnd, BOr, BXor, BNot, Shl, Shr, PopCount,
+    I, J,
+    Break, Continue,
+    Del,
+}
+
+impl Op {
+
+    // Retrouve l'instruction à partir de son nom ou de son symbole (déjà en minuscules)
+    pub fn from_word(word: &str) -> Option<Op> {
+        let op = match word {
+            "+" | "add" => Op::Add,
+            "-" | "sub" => Op::Sub,
+            "*" | "mul" => Op::Mul,
+            "/" | "div" => Op::Div,
+            "^" | "pow" => Op::Pow,
+            "mod"       => Op::Mod,
+            "rem"       => Op::Rem,
+            "fdiv"      => Op::FDiv,
+            "divmod"    => Op::DivMod,
+            "eq"        => Op::Eq,
+            "ne"        => Op::Ne,
+            "lt"        => Op::Lt,
+            "le"        => Op::Le,
+            "gt"        => Op::Gt,
+            "ge"        => Op::Ge,
+            "and"       => Op::And,
+            "or"        => Op::Or,
+            "~" | "dup" => Op::Dup,
+            "<" | "dpl" => Op::DpL,
+            ">" | "dpr" => Op::DpR,
+            "$" | "sqr" => Op::Sqr,
+            "log"       => Op::Log,
+            "exp"       => Op::Exp,
+            "sin"       => Op::Sin,
+            "cos"       => Op::Cos,
+            "tan"       => Op::Tan,
+            "floor"     => Op::Floor,
+            "ceil"      => Op::Ceil,
+            "round"     => Op::Round,
+            "not"       => Op::Not,
+            "gcd"       => Op::Gcd,
+            "lcm"       => Op::Lcm,
+            "abs"       => Op::Abs,
+            "min"       => Op::Min,
+            "max"       => Op::Max,
+            "fact"      => Op::Fact,
+            "choose"    => Op::Choose,
+            "isprime"   => Op::IsPrime,
+            "nextprime" => Op::NextPrime,
+            "modpow"    => Op::ModPow,
+            "band"      => Op::BAnd,
+            "bor"       => Op::BOr,
+            "bxor"      => Op::BXor,
+            "bnot"      => Op::BNot,
+            "shl"       => Op::Shl,
+            "shr"       => Op::Shr,
+            "popcount"  => Op::PopCount,
+            "i"         => Op::I,
+            "j"         => Op::J,
+            "break"     => Op::Break,
+            "continue"  => Op::Continue,
+            "!" | "del" => Op::Del,
+            _ => return None,
+        };
+        return Some(op);
+    }
+}
+
+// Une fonction définie par l'utilisateur : son code (pour l'affichage, et vérifier les redéfinitions) et son arbre
+#[derive(Clone, Debug)]
+pub struct Function {
+    pub code: String,
+    pub body: Rc<Vec<Node>>,
+}
+
+// Un noeud du programme. "span" est la position du début du noeud dans le code (pour les messages d'erreurs),
+// "side" le "<" ou ">" écrit devant (None si rien n'est précisé)
+#[derive(Clone, Debug)]
+pub enum Node {
+    Op { op: Op, side: Option<Side>, span: Span },
+    Number { text: String, side: Option<Side>, span: Span }, // Lu à l'execution, dans le mode de l'interpréteur à ce moment
+    Call { name: String, span: Span }, // Appel d'une fonction, qui peut etre définie plus tard
+    For { side: Option<Side>, body: Vec<Node>, span: Span },
+    If { side: Option<Side>, then: Vec<Node>, otherwise: Vec<Node>, span: Span },
+    Range { side: Option<Side>, body: Vec<Node>, span: Span },
+    While { side: Option<Side>, condition: Vec<Node>, body: Vec<Node>, span: Span },
+    Define { name: String, function: Function, span: Span },
+    Overflow { policy: OverflowPolicy, span: Span }, // La directive "#!overflow <politique>", qui vaut à partir de là
+}
+
+// Analyse tout un code Esianolop
+pub fn parse(text: &str) -> Result<Vec<Node>, EsianolopError> {
+    // Les commentaires ne servent pas à l'execution, sauf les directives "#!overflow"
+    // (les autres "#!", comme un shebang, restent de simples commentaires)
+    let tokens = lexer::tokenize(text).into_iter()
+        .filter(|token| !matches!(&token.kind, TokenKind::Comment(e) if !e.starts_with("#!overflow")))
+        .collect::<Vec<Token>>();
+    return parse_tokens(text, &tokens);
+}
+
+// Analyse une suite de tokens de "text" (tout le code, ou le corps d'une boucle / d'un if / d'une fonction)
+fn parse_tokens(text: &str, tokens: &[Token]) -> Result<Vec<Node>, EsianolopError> {
+    let mut nodes = vec![];
+    // On avance dans les tokens avec un curseur, ce qui permet de sauter les définitions entières
+    let mut cursor = 0;
+
+    while cursor < tokens.len() {
+        let start = &tokens[cursor];
+        let span = start.span;
+
+        // On test si il y a un "<" ou ">" devant
+        let (side, word_index) = match start.kind {
+            TokenKind::Side(side) => (Some(side), cursor + 1),
+            _ => (None, cursor),
+        };
+        let word = match tokens.get(word_index) {
+            Some(e) => e,
+            None => return Err(malformed(format!("nothing after '{}'", start.kind), span)),
+        };
+
+        // Si c'est une définition de fonction/for/range/if/while, on prend son code j'usqu'au ':' suivant
+        if let (TokenKind::Identifier(name), Some(TokenKind::Colon)) = (&word.kind, tokens.get(word_index + 1).map(|e| &e.kind)) {
+
+            let (code, after) = split_definition(tokens, word_index + 2).map_err(|e| e.at(span))?;
+            let body = parse_tokens(text, &tokens[code.clone()])?;
+            cursor = after;
+
+            let node = match name.as_str() {
+                "for" => Node::For { side, body, span },
+                "range" => Node::Range { side, body, span },
+                // Le if et le while ont deux codes à la suite : "if:alors:sinon:" et "while:condition:corps:"
+                "if" | "while" => {
+                    let (second, after_second) = split_definition(tokens, after).map_err(|e| e.at(span))?;
+                    let second = parse_tokens(text, &tokens[second])?;
+                    cursor = after_second;
+                    if name == "if" {
+                        Node::If { side, then: body, otherwise: second, span }
+                    } else {
+                        Node::While { side, condition: body, body: second, span }
+                    }
+                },
+                // Sinon, c'est la définition d'une fonction
+                _ => {
+                    // Un "<" ou ">" n'a pas de sens devant un nom de fonction
+                    if side.is_some() {
+                        return Err(malformed(format!("a function name can't start with '{}'", start.kind), span));
+                    }
+                    // Une instruction passe toujours avant une fonction : une fonction du meme nom ne serait jamais appelée
+                    if Op::from_word(name).is_some() {
+                        return Err(malformed(format!("'{}' is an instruction, it can't be a function name", name), span));
+                    }
+                    // Si le code de la fonction est vide
+                    let code = match (tokens[code.clone()].first(), tokens[code].last()) {
+                        (Some(first), Some(last)) => text[first.span.start..last.span.end].to_owned(),
+                        _ => return Err(malformed("trying to define an empty function".to_owned(), span)),
+                    };
+                    Node::Define { name: name.to_owned(), function: Function { code, body: Rc::new(body) }, span }
+                },
+            };
+            nodes.push(node);
+            continue;
+        }
+
+        cursor = word_index + 1;
+
+        let node = match &word.kind {
+            // Le nombre n'est lu qu'à l'execution : le corps d'une fonction suit le mode en place quand elle est appelée
+            TokenKind::Number(e) => Node::Number { text: e.to_owned(), side, span },
+            TokenKind::Identifier(e) | TokenKind::Operator(e) => match Op::from_word(e) {
+                Some(op) => Node::Op { op, side, span },
+                // Un nom inconnu est un appel de fonction, elle sera cherchée à l'execution
+                None if matches!(word.kind, TokenKind::Identifier(_)) => Node::Call { name: e.to_owned(), span },
+                None => return Err(EsianolopError::new(EsianolopErrorKind::UnknownWord(e.to_owned())).at(span)),
+            },
+            // Une directive "#!overflow <politique>" (par exemple "#!overflow mod 1000000007")
+            TokenKind::Comment(e) => match OverflowPolicy::from_name(&e["#!overflow".len()..]) {
+                Some(policy) => Node::Overflow { policy, span },
+                None => return Err(EsianolopError::new(EsianolopErrorKind::InvalidDirective(format!("'{}' is not a valid overflow policy (checked, wrapping, saturating, mod N)", e["#!overflow".len()..].trim()))).at(word.span)),
+            },
+            // Un ':' ou un bloc [ ] qui n'est pas dans une définition
+            other => return Err(malformed(format!("unexpected '{}'", other), word.span)),
+        };
+        nodes.push(node);
+    }
+    return Ok(nodes);
+}
+
+// Une erreur de syntaxe à la position donnée
+fn malformed(what: String, span: Span) -> EsianolopError {
+    EsianolopError::new(EsianolopErrorKind::MalformedDefinition(what)).at(span)
+}
+
+// Coupe le code d'une définition qui commence au token "start" au ':' qui la termine (meme sur une autre ligne).
+// Retourne (les tokens du code, le token d'après). Sans ':' final, la définition va jusqu'à la fin du code.
+// Si le code commence par '[', il va jusqu'au ']' correspondant : il peut alors contenir des ':' et d'autres blocs [ ],
+// ce qui permet d'imbriquer les for, if, fonctions... Le ':' juste après le ']' est optionnel.
+fn split_definition(tokens: &[Token], start: usize) -> Result<(Range<usize>, usize), EsianolopError> {
+    if let Some(TokenKind::OpenBlock) = tokens.get(start).map(|e| &e.kind) {
+        let mut depth = 0;
+        for (i, token) in tokens.iter().enumerate().skip(start) {
+            match token.kind {
+                TokenKind::OpenBlock => depth += 1,
+                TokenKind::CloseBlock => depth -= 1,
+                _ => (),
+            }
+            if depth == 0 {
+                let after = if let Some(TokenKind::Colon) = tokens.get(i + 1).map(|e| &e.kind) { i + 2 } else { i + 1 };
+                return Ok((start + 1..i, after));
+            }
+        }
+        return Err(EsianolopError::new(EsianolopErrorKind::MalformedDefinition("missing ']' to close block".to_owned())));
+    }
+    match tokens.iter().skip(start).position(|e| e.kind == TokenKind::Colon) {
+        Some(e) => Ok((start..start + e, start + e + 1)),
+        None => Ok((start..tokens.len(), tokens.len())),
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Op::Add => "add", Op::Sub => "sub", Op::Mul => "mul", Op::Div => "div", Op::Pow => "pow",
+            Op::Mod => "mod", Op::Rem => "rem", Op::FDiv => "fdiv", Op::DivMod => "divmod",
+            Op::Eq => "eq", Op::Ne => "ne", Op::Lt => "lt", Op::Le => "le", Op::Gt => "gt", Op::Ge => "ge",
+            Op::And => "and", Op::Or => "or",
+            Op::Dup => "dup", Op::DpL => "dpl", Op::DpR => "dpr",
+            Op::Sqr => "sqr", Op::Log => "log", Op::Exp => "exp", Op::Sin => "sin", Op::Cos => "cos", Op::Tan => "tan",
+            Op::Floor => "floor", Op::Ceil => "ceil", Op::Round => "round", Op::Not => "not",
+            Op::Gcd => "gcd", Op::Lcm => "lcm", Op::Abs => "abs", Op::Min => "min", Op::Max => "max",
+            Op::Fact => "fact", Op::Choose => "choose", Op::IsPrime => "isprime", Op::NextPrime => "nextprime", Op::ModPow => "modpow",
+            Op::BAnd => "band", Op::BOr => "bor", Op::BXor => "bxor", Op::BNot => "bnot",
+            Op::Shl => "shl", Op::Shr => "shr", Op::PopCount => "popcount",
+            Op::I => "i", Op::J => "j",
+            Op::Break => "break", Op::Continue => "continue",
+            Op::Del => "del",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,619 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+// Entier de taille arbitraire, fait maison (pas de crate externe)
+// Le nombre est stoqué en "chiffres" de 32 bits (base 2^32), du plus petit au plus grand, avec un signe à coté.
+// Zéro s'écrit avec une liste vide et un signe positif, et il n'y a jamais de zéros inutiles à la fin de la liste.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>,
+}
+
+// ----- Fonctions sur les valeurs absolues (listes de chiffres) -----
+
+// Retire les zéros inutiles en fin de liste
+fn trim(digits: &mut Vec<u32>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+fn cmp_mag(a: &[u32], b: &[u32]) -> Ordering {
+    if a.len() != b.len() {
+        return a.len().cmp(&b.len());
+    }
+    for (x, y) in a.iter().rev().zip(b.iter().rev()) {
+        if x != y {
+            return x.cmp(y);
+        }
+    }
+    return Ordering::Equal;
+}
+
+fn add_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut res = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, x) in long.iter().enumerate() {
+        let sum = *x as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        res.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry != 0 {
+        res.push(carry as u32);
+    }
+    return res;
+}
+
+// Soustraction de valeurs absolues, a doit etre plus grand ou égal à b
+fn sub_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut res = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, x) in a.iter().enumerate() {
+        let mut diff = *x as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += 1 << 32;
+            borrow = 1;
+        }
+        res.push(diff as u32);
+    }
+    trim(&mut res);
+    return res;
+}
+
+fn mul_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let mut res = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, y) in b.iter().enumerate() {
+            let cur = res[i + j] as u64 + (*x as u64) * (*y as u64) + carry;
+            res[i + j] = cur as u32;
+            carry = cur >> 32;
+        }
+        res[i + b.len()] = carry as u32;
+    }
+    trim(&mut res);
+    return res;
+}
+
+// Division par un seul chiffre, retourne (quotient, reste)
+fn divrem_small(a: &[u32], d: u32) -> (Vec<u32>, u32) {
+    let mut res = vec![0u32; a.len()];
+    let mut rem = 0u64;
+    for i in (0..a.len()).rev() {
+        let cur = (rem << 32) | a[i] as u64;
+        res[i] = (cur / d as u64) as u32;
+        rem = cur % d as u64;
+    }
+    trim(&mut res);
+    return (res, rem as u32);
+}
+
+// Décale les chiffres de "shift" bits vers la gauche (shift < 32), en rajoutant un chiffre si besoin
+fn shl_bits(a: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return a.to_vec();
+    }
+    let mut res = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0u32;
+    for x in a {
+        res.push((x << shift) | carry);
+        carry = x >> (32 - shift);
+    }
+    res.push(carry);
+    return res;
+}
+
+// Division longue (algorithme D de Knuth), retourne (quotient, reste)
+fn divrem_mag(u: &[u32], v: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_mag(u, v) == Ordering::Less {
+        return (vec![], u.to_vec());
+    }
+    if v.len() == 1 {
+        let (q, r) = divrem_small(u, v[0]);
+        return (q, if r == 0 { vec![] } else { vec![r] });
+    }
+
+    // On normalise pour que le plus grand chiffre du diviseur ait son bit de poids fort à 1
+    let shift = v[v.len() - 1].leading_zeros();
+    let mut vn = shl_bits(v, shift);
+    vn.truncate(v.len());
+    let mut un = shl_bits(u, shift);
+    if un.len() == u.len() {
+        un.push(0);
+    }
+
+    let n = v.len();
+    let m = u.len() - n;
+    let mut q = vec![0u32; m + 1];
+    let base = 1u64 << 32;
+
+    for j in (0..=m).rev() {
+        // Estimation du chiffre du quotient avec les deux premiers chiffres
+        let num = ((un[j + n] as u64) << 32) | un[j + n - 1] as u64;
+        let mut qhat = num / vn[n - 1] as u64;
+        let mut rhat = num % vn[n - 1] as u64;
+        while qhat >= base || qhat * vn[n - 2] as u64 > ((rhat << 32) | un[j + n - 2] as u64) {
+            qhat -= 1;
+            rhat += vn[n - 1] as u64;
+            if rhat >= base {
+                break;
+            }
+        }
+
+        // On multiplie et soustrait
+        let mut k = 0i64;
+        for i in 0..n {
+            let p = qhat * vn[i] as u64;
+            let t = un[i + j] as i64 - k - (p & 0xFFFF_FFFF) as i64;
+            un[i + j] = t as u32;
+            k = (p >> 32) as i64 - (t >> 32);
+        }
+        let t = un[j + n] as i64 - k;
+        un[j + n] = t as u32;
+
+        // Si on a soustrait une fois de trop, on rajoute le diviseur
+        q[j] = qhat as u32;
+        if t < 0 {
+            q[j] = q[j].wrapping_sub(1);
+            let mut carry = 0u64;
+            for i in 0..n {
+                let t = un[i + j] as u64 + vn[i] as u64 + carry;
+                un[i + j] = t as u32;
+                carry = t >> 32;
+            }
+            un[j + n] = un[j + n].wrapping_add(carry as u32);
+        }
+    }
+
+    // Le reste est dans un, il faut enlever la normalisation
+    let mut r = vec![0u32; n];
+    for i in 0..n {
+        r[i] = if shift == 0 { un[i] } else { (un[i] >> shift) | (un[i + 1] << (32 - shift)) };
+    }
+    trim(&mut q);
+    trim(&mut r);
+    return (q, r);
+}
+
+// Passe des chiffres en complément à deux à leur opposé (ou l'inverse) : on inverse les bits et on ajoute 1
+fn negate_twos(digits: &mut [u32]) {
+    let mut carry = 1u64;
+    for d in digits.iter_mut() {
+        let cur = (!*d) as u64 + carry;
+        *d = cur as u32;
+        carry = cur >> 32;
+    }
+}
+
+// ----- Le BigInt en lui même -----
+
+impl BigInt {
+
+    fn from_parts(negative: bool, mut digits: Vec<u32>) -> BigInt {
+        trim(&mut digits);
+        let negative = negative && !digits.is_empty(); // Pas de zéro négatif
+        BigInt { negative, digits }
+    }
+
+    pub fn zero() -> BigInt {
+        BigInt { negative: false, digits: vec![] }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt { negative: false, digits: self.digits.clone() }
+    }
+
+    // Retourne la valeur si elle tient dans un i64
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 {
+            return None;
+        }
+        let mag = self.digits.iter().rev().fold(0u64, |acc, x| (acc << 32) | *x as u64);
+        if self.negative {
+            if mag <= i64::MAX as u64 + 1 { Some((mag as i64).wrapping_neg()) } else { None }
+        } else {
+            i64::try_from(mag).ok()
+        }
+    }
+
+    // Valeur approchée (infinie si le nombre est trop grand)
+    pub fn to_f64(&self) -> f64 {
+        let mag = self.digits.iter().rev().fold(0f64, |acc, x| acc * 4294967296.0 + *x as f64);
+        if self.negative { -mag } else { mag }
+    }
+
+    // Partie entière (tronquée vers zéro) d'un flottant. None pour l'infini ou NaN.
+    pub fn from_f64(val: f64) -> Option<BigInt> {
+        if !val.is_finite() {
+            return None;
+        }
+        let val = val.trunc();
+        if val.abs() < 9.0e18 {
+            return Some(BigInt::from(val as i64));
+        }
+        // Les grands flottants sont des entiers de la forme mantisse * 2^exposant
+        let bits = val.to_bits();
+        let exp = ((bits >> 52) & 0x7FF) as u32 - 1075;
+        let mantissa = ((bits & 0xF_FFFF_FFFF_FFFF) | (1 << 52)) as i64;
+        let mag = &BigInt::from(mantissa) * &BigInt::from(2).pow(exp);
+        return Some(if val < 0.0 { -&mag } else { mag });
+    }
+
+    // Division entière tronquée (vers zéro), comme pour les i64. Retourne None si on divise par zéro.
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (q, r) = divrem_mag(&self.digits, &other.digits);
+        return Some((
+            BigInt::from_parts(self.negative != other.negative, q),
+            BigInt::from_parts(self.negative, r),
+        ));
+    }
+
+    // Plus grand diviseur commun (toujours positif), par l'algorithme d'Euclide
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let mut a = self.abs();
+        let mut b = other.abs();
+        while !b.is_zero() {
+            let (_, r) = divrem_mag(&a.digits, &b.digits);
+            a = b;
+            b = BigInt::from_parts(false, r);
+        }
+        return a;
+    }
+
+    pub fn pow(&self, mut exp: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut res = BigInt::from(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res = &res * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        return res;
+    }
+
+    // Racine carrée entière (arrondie en dessous) par la méthode de Newton. Retourne None pour un nombre négatif.
+    pub fn sqrt(&self) -> Option<BigInt> {
+        if self.negative {
+            return None;
+        }
+        if self.is_zero() {
+            return Some(BigInt::zero());
+        }
+        // On part d'une valeur forcément plus grande que la racine : 2^(nb_bits/2 + 1)
+        let bits = self.digits.len() as u32 * 32 - self.digits[self.digits.len() - 1].leading_zeros();
+        let mut x = BigInt::from(2).pow(bits / 2 + 1);
+        loop {
+            let (q, _) = self.div_rem(&x)?;
+            let (y, _) = (&x + &q).div_rem(&BigInt::from(2))?;
+            if y >= x {
+                return Some(x);
+            }
+            x = y;
+        }
+    }
+
+    // Le nombre de bits de la valeur absolue (0 pour zéro)
+    pub fn bits(&self) -> u64 {
+        match self.digits.last() {
+            Some(last) => self.digits.len() as u64 * 32 - last.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    // Le nombre de bits à 1 de la valeur absolue
+    pub fn count_ones(&self) -> u64 {
+        self.digits.iter().map(|x| x.count_ones() as u64).sum()
+    }
+
+    // Les chiffres en complément à deux, sur "len" chiffres (len doit laisser la place au bit de signe)
+    fn to_twos(&self, len: usize) -> Vec<u32> {
+        let mut res = self.digits.clone();
+        res.resize(len, 0);
+        if self.negative {
+            negate_twos(&mut res);
+        }
+        return res;
+    }
+
+    // Applique "op" chiffre par chiffre, en complément à deux : un négatif est vu comme ayant une infinité de 1 devant
+    pub fn bitwise(&self, other: &BigInt, op: fn(u32, u32) -> u32) -> BigInt {
+        let len = std::cmp::max(self.digits.len(), other.digits.len()) + 1;
+        let mut res = self.to_twos(len).iter().zip(other.to_twos(len).iter()).map(|(a, b)| op(*a, *b)).collect::<Vec<u32>>();
+        // Le bit de poids fort donne le signe du résultat
+        let negative = res[len - 1] >> 31 == 1;
+        if negative {
+            negate_twos(&mut res);
+        }
+        return BigInt::from_parts(negative, res);
+    }
+
+    // Multiplie par 2^shift (décalage à gauche)
+    pub fn shl(&self, shift: u32) -> BigInt {
+        let mut digits = vec![0u32; (shift / 32) as usize];
+        digits.extend(shl_bits(&self.digits, shift % 32));
+        return BigInt::from_parts(self.negative, digits);
+    }
+
+    // Divise par 2^shift en arrondissant en dessous (décalage à droite arithmétique : -1 >> n donne toujours -1)
+    pub fn shr(&self, shift: u64) -> BigInt {
+        if shift >= self.bits() {
+            return BigInt::from(if self.negative { -1 } else { 0 });
+        }
+        let (words, bits) = ((shift / 32) as usize, (shift % 32) as u32);
+        let mut digits = self.digits[words..].to_vec();
+        // Les bits qui disparaissent : pour un négatif, s'il y en a un à 1 on arrondit en dessous
+        let lost = self.digits[..words].iter().any(|x| *x != 0) || (bits > 0 && digits[0] << (32 - bits) != 0);
+        if bits > 0 {
+            for i in 0..digits.len() {
+                digits[i] = (digits[i] >> bits) | digits.get(i + 1).map(|x| x << (32 - bits)).unwrap_or(0);
+            }
+        }
+        let res = BigInt::from_parts(self.negative, digits);
+        if self.negative && lost { &res - &BigInt::from(1) } else { res }
+    }
+
+    // Les 64 bits du bas, en complément à deux (comme "as u64" en rust)
+    pub fn low_u64(&self) -> u64 {
+        let mag = self.digits.iter().take(2).rev().fold(0u64, |acc, x| (acc << 32) | *x as u64);
+        if self.negative { mag.wrapping_neg() } else { mag }
+    }
+
+    // Reste de la division par "modulus", toujours entre 0 et modulus-1 (modulus doit etre positif)
+    fn rem_positive(&self, modulus: &BigInt) -> BigInt {
+        let (_, r) = self.div_rem(modulus).unwrap();
+        if r.is_negative() { &r + modulus } else { r }
+    }
+
+    // self^exp modulo "modulus", par carrés successifs (en lisant les bits de l'exposant).
+    // None si l'exposant est négatif ou si modulus n'est pas positif.
+    pub fn modpow(&self, exp: &BigInt, modulus: &BigInt) -> Option<BigInt> {
+        if exp.negative || modulus.negative || modulus.is_zero() {
+            return None;
+        }
+        let mut base = self.rem_positive(modulus);
+        let mut res = BigInt::from(1).rem_positive(modulus);
+        for digit in &exp.digits {
+            for bit in 0..32 {
+                if (digit >> bit) & 1 == 1 {
+                    res = (&res * &base).rem_positive(modulus);
+                }
+                base = (&base * &base).rem_positive(modulus);
+            }
+        }
+        return Some(res);
+    }
+
+    // Test de primalité de Miller-Rabin, avec les 12 premiers nombres premiers comme témoins :
+    // c'est exact jusqu'à 3.3 * 10^24, et au delà la probabilité d'erreur est négligeable
+    pub fn is_probable_prime(&self) -> bool {
+        let one = BigInt::from(1);
+        let two = BigInt::from(2);
+        if self < &two {
+            return false;
+        }
+        for p in &[2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+            let p = BigInt::from(*p);
+            if self == &p {
+                return true;
+            }
+            if self.rem_positive(&p).is_zero() {
+                return false;
+            }
+        }
+        // self - 1 = d * 2^s, avec d impair
+        let minus_one = self - &one;
+        let mut d = minus_one.clone();
+        let mut s = 0;
+        while d.digits[0] & 1 == 0 {
+            d = d.div_rem(&two).unwrap().0;
+            s += 1;
+        }
+        'witness: for a in &[2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+            let mut x = BigInt::from(*a).modpow(&d, self).unwrap();
+            if x == one || x == minus_one {
+                continue;
+            }
+            for _ in 1..s {
+                x = (&x * &x).rem_positive(self);
+                if x == minus_one {
+                    continue 'witness;
+                }
+            }
+            return false;
+        }
+        return true;
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(val: i64) -> BigInt {
+        let mag = val.unsigned_abs();
+        BigInt::from_parts(val < 0, vec![mag as u32, (mag >> 32) as u32])
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ();
+
+    // Lecture d'un nombre en base 10, avec éventuellement un signe devant
+    fn from_str(text: &str) -> Result<BigInt, ()> {
+        let (negative, digits) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+            return Err(());
+        }
+        // On lit 9 chiffres d'un coup (10^9 tient dans un u32)
+        let mut mag: Vec<u32> = vec![];
+        for chunk in digits.as_bytes().chunks(9) {
+            let chunk_val = chunk.iter().fold(0u64, |acc, c| acc * 10 + (c - b'0') as u64);
+            let mut carry = chunk_val;
+            for d in mag.iter_mut() {
+                let cur = *d as u64 * 10u64.pow(chunk.len() as u32) + carry;
+                *d = cur as u32;
+                carry = cur >> 32;
+            }
+            if carry != 0 {
+                mag.push(carry as u32);
+            }
+        }
+        return Ok(BigInt::from_parts(negative, mag));
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // On découpe en paquets de 9 chiffres décimaux
+        let mut chunks = vec![];
+        let mut mag = self.digits.clone();
+        while !mag.is_empty() {
+            let (q, r) = divrem_small(&mag, 1_000_000_000);
+            chunks.push(r);
+            mag = q;
+        }
+        let mut res = String::new();
+        if self.negative {
+            res.push('-');
+        }
+        res += &chunks[chunks.len() - 1].to_string();
+        for chunk in chunks.iter().rev().skip(1) {
+            res += &format!("{:09}", chunk);
+        }
+        return write!(f, "{}", res);
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_mag(&self.digits, &other.digits),
+            (true, true) => cmp_mag(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.digits.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_mag(&self.digits, &other.digits));
+        }
+        // Signes différents : on soustrait le plus petit du plus grand, et on garde le signe du plus grand
+        match cmp_mag(&self.digits, &other.digits) {
+            Ordering::Less => BigInt::from_parts(other.negative, sub_mag(&other.digits, &self.digits)),
+            _ => BigInt::from_parts(self.negative, sub_mag(&self.digits, &other.digits)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &(-other)
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(self.negative != other.negative, mul_mag(&self.digits, &other.digits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(text: &str) -> BigInt {
+        text.parse().unwrap()
+    }
+
+    // Compare la division à celle des i128 (troncature vers 0, le reste a le signe du dividende)
+    fn check_i128(u: i128, v: i128) {
+        let (q, r) = big(&u.to_string()).div_rem(&big(&v.to_string())).unwrap();
+        assert_eq!((q.to_string(), r.to_string()), ((u / v).to_string(), (u % v).to_string()), "{} / {}", u, v);
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert!(big("12").div_rem(&BigInt::zero()).is_none());
+    }
+
+    #[test]
+    fn multi_limb_divisor() {
+        check_i128(0x1234_5678_9abc_def0_1122_3344_5566_7788, 0x1_0000_0003);
+        check_i128((1 << 96) - 1, (1 << 64) + 3);
+        check_i128(i128::MAX, 0xffff_ffff_ffff_ffff_ffff);
+        // Le dividende plus petit que le diviseur
+        check_i128(0xffff_ffff_ffff, 0x1_0000_0000_0000_0000);
+    }
+
+    #[test]
+    fn add_back_step() {
+        // Le premier chiffre estimé du quotient est trop grand d'un : il faut rajouter le diviseur une fois
+        check_i128(0x7fff_ffff_8000_0000_0000_0000_0000_0000, 0x8000_0000_0000_0000_0000_0001);
+        check_i128(0x8000_0000_0000_0000_0000_0003, 0x2000_0000_0000_0000_0000_0001);
+    }
+
+    #[test]
+    fn negative_operands() {
+        for &(u, v) in &[(0x7fff_ffff_8000_0000_0000_0000_0000_0000, 0x8000_0000_0000_0000_0000_0001), ((1 << 96) - 1, (1 << 64) + 3), (7, 2)] {
+            check_i128(-u, v);
+            check_i128(u, -v);
+            check_i128(-u, -v);
+        }
+    }
+
+    #[test]
+    fn larger_than_i128() {
+        let u = &big("3").pow(200) + &big("12345");
+        let v = &big("7").pow(40) - &big("1");
+        let (q, r) = u.div_rem(&v).unwrap();
+        assert_eq!(&(&q * &v) + &r, u);
+        assert!(!r.is_negative() && r < v);
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::{Node, Op};
+use crate::lexer::{Side, Span};
+use crate::value::OverflowPolicy;
+
+// Le bytecode : l'arbre du programme (voir ast.rs) est aplati en une liste d'instructions simples,
+// que la machine virtuelle de l'interpréteur execute une par une. Les boucles et les if deviennent des sauts.
+
+#[derive(Clone, Debug)]
+pub enum Opcode {
+    Push { text: String, front: bool, span: Span },    // Ajoute un nombre au stack (devant si front), lu dans le mode de l'interpréteur
+    Op { op: Op, side: Option<Side>, span: Span },     // Une instruction de base
+    Call { name: String, span: Span },                 // Appel d'une fonction
+    Define { name: String, function: Function, span: Span },
+    If { side: Option<Side>, otherwise: usize, span: Span }, // Retire une valeur, saute à "otherwise" si elle est nulle
+    Jump(usize),
+    // Début d'une boucle : "end" est l'instruction juste après son Next
+    For { side: Option<Side>, end: usize, span: Span },
+    Range { side: Option<Side>, end: usize, span: Span },
+    While { end: usize, span: Span },
+    WhileTest { side: Option<Side> },                  // Après la condition du while : retire une valeur, sort de la boucle si elle est nulle
+    Next,                                              // Fin du corps d'une boucle : passe au tour suivant
+    Overflow(OverflowPolicy),                          // Change la politique de débordement (directive "#!overflow")
+}
+
+// Une fonction compilée : son code source (pour l'affichage, et vérifier les redéfinitions), son bytecode
+// et l'endroit où elle est définie (pour la trace des erreurs)
+#[derive(Clone, Debug)]
+pub struct Function {
+    pub code: String,
+    pub bytecode: Rc<Vec<Opcode>>,
+    pub span: Span,
+}
+
+// Compile un programme (ou un corps de fonction) en bytecode
+pub fn compile(nodes: &[Node]) -> Vec<Opcode> {
+    let mut code = vec![];
+    compile_into(nodes, &mut code);
+    return code;
+}
+
+fn compile_into(nodes: &[Node], code: &mut Vec<Opcode>) {
+    for node in nodes {
+        match node {
+            Node::Op { op, side, span } => code.push(Opcode::Op { op: *op, side: *side, span: *span }),
+            // Par défault, la position d'un nombre est à droite, et pas à gauche
+            Node::Number { text, side, span } => code.push(Opcode::Push { text: text.to_owned(), front: *side == Some(Side::Left), span: *span }),
+            Node::Call { name, span } => code.push(Opcode::Call { name: name.to_owned(), span: *span }),
+            Node::Overflow { policy, .. } => code.push(Opcode::Overflow(*policy)),
+            Node::Define { name, function, span } => {
+                let function = Function { code: function.code.to_owned(), bytecode: Rc::new(compile(&function.body)), span: *span };
+                code.push(Opcode::Define { name: name.to_owned(), function, span: *span });
+            },
+            Node::If { side, then, otherwise, span } => {
+                // if → then... Jump(fin) → otherwise...
+                let start = code.len();
+                code.push(Opcode::Jump(0));
+                compile_into(then, code);
+                let jump = code.len();
+                code.push(Opcode::Jump(0));
+                compile_into(otherwise, code);
+                code[start] = Opcode::If { side: *side, otherwise: jump + 1, span: *span };
+                code[jump] = Opcode::Jump(code.len());
+            },
+            // Les boucles : début → corps... → Next, la fin étant connue une fois le corps compilé
+            Node::For { side, body, span } | Node::Range { side, body, span } => {
+                let start = code.len();
+                code.push(Opcode::Next);
+                compile_into(body, code);
+                code.push(Opcode::Next);
+                let end = code.len();
+                code[start] = match node {
+                    Node::For { .. } => Opcode::For { side: *side, end, span: *span },
+                    _ => Opcode::Range { side: *side, end, span: *span },
+                };
+            },
+            // Le while : début → condition... → WhileTest → corps... → Next
+            Node::While { side, condition, body, span } => {
+                let start = code.len();
+                code.push(Opcode::Next);
+                compile_into(condition, code);
+                code.push(Opcode::WhileTest { side: *side });
+                compile_into(body, code);
+                code.push(Opcode::Next);
+                code[start] = Opcode::While { end: code.len(), span: *span };
+            },
+        }
+    }
+}
+
+// Affiche le bytecode, une instruction par ligne avec son numéro (pour la commande "disasm")
+pub fn disassemble(code: &[Opcode]) -> String {
+    return code.iter().enumerate()
+        .map(|(i, opcode)| format!("{:04}  {}", i, opcode))
+        .collect::<Vec<String>>().join("\n");
+}
+
+// Le "<" / ">" devant une instruction, s'il est précisé
+fn side_prefix(side: &Option<Side>) -> &'static str {
+    match side {
+        Some(Side::Left) => "<",
+        Some(Side::Right) => ">",
+        None => "",
+    }
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Opcode::Push { text, front, .. } => write!(f, "push {}{}", if *front { "<" } else { "" }, text),
+            Opcode::Op { op, side, .. } => write!(f, "op {}{}", side_prefix(side), op),
+            Opcode::Call { name, .. } => write!(f, "call {}", name),
+            Opcode::Define { name, function, .. } => write!(f, "define {} ({} instructions)", name, function.bytecode.len()),
+            Opcode::If { side, otherwise, .. } => write!(f, "{}if else {:04}", side_prefix(side), otherwise),
+            Opcode::Jump(target) => write!(f, "jump {:04}", target),
+            Opcode::For { side, end, .. } => write!(f, "{}for end {:04}", side_prefix(side), end),
+            Opcode::Range { side, end, .. } => write!(f, "{}range end {:04}", side_prefix(side), end),
+            Opcode::While { end, .. } => write!(f, "while end {:04}", end),
+            Opcode::WhileTest { side } => write!(f, "{}while test", side_prefix(side)),
+            Opcode::Next => write!(f, "next"),
+            Opcode::Overflow(policy) => write!(f, "overflow {}", policy),
+        }
+    }
+}
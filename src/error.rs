@@ -0,0 +1,134 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::lexer::Span;
+use crate::value::EsianolopMode;
+
+// Les erreurs de l'interpréteur : un type d'erreur qu'on peut tester avec un match (au lieu de lire le message),
+// l'endroit du code où elle a eu lieu, et la trace des appels de fonctions et des boucles en cours.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EsianolopErrorKind {
+    Overflow(&'static str),       // Le résultat ne rentre pas dans le type (l'opération en question : "addition"...)
+    NotFinite(&'static str),      // Un calcul en flottant donne l'infini ou NaN
+    DivisionByZero,
+    Negative(&'static str),       // Une valeur négative là où c'est interdit ("square-root"...)
+    InvalidOperand(&'static str), // Une valeur qui n'a pas de sens pour l'opération (exposant à virgule, racine irrationnelle...)
+    NotInteger(&'static str),     // Une opération sur les entiers (gcd, fact...) appliquée à une fraction ou un flottant à virgule
+    StackUnderflow(String),       // Pas assez de valeurs dans le stack pour l'instruction
+    UnknownWord(String),          // Ni une instruction, ni une fonction définie
+    InvalidNumber { text: String, mode: EsianolopMode }, // Un nombre qui ne peut pas etre lu dans le mode de l'interpréteur
+    Redefinition(String),         // Une fonction redéfinie avec un autre code
+    MalformedDefinition(String),  // Une définition ou un bloc [ ] mal écrit
+    OutsideLoop(String),          // "i", "j", "break" ou "continue" sans boucle autour
+    InvalidLoop(String),          // Un nombre de tours ou un pas de boucle impossible
+    InvalidDirective(String),     // Une directive "#!..." inconnue ou mal écrite
+    Io(String),                   // Le fichier n'a pas pu etre lu
+}
+
+// Un appel de fonction ou une boucle en cours au moment de l'erreur.
+// Pour un appel, "span" est l'endroit de l'appel et "defined" celui de la définition de la fonction ;
+// pour une boucle, "span" est l'endroit de la boucle et "iteration" le tour en cours (en partant de 1).
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraceEntry {
+    Call { name: String, span: Span, defined: Span },
+    Loop { name: &'static str, span: Span, iteration: usize },
+}
+
+// avec  kind : Le type d'erreur
+// et    span : L'instruction qui a raté (None si l'erreur vient du calcul d'un arbre du stack)
+// et    note : Ce que faisait l'instruction, si besoin ("in duplicate left to right"...)
+// et   trace : Les appels et boucles en cours, du plus profond au moins profond
+#[derive(Clone, Debug, PartialEq)]
+pub struct EsianolopError {
+    pub kind: EsianolopErrorKind,
+    pub span: Option<Span>,
+    pub note: Option<String>,
+    pub trace: Vec<TraceEntry>,
+}
+
+impl EsianolopError {
+
+    pub fn new(kind: EsianolopErrorKind) -> EsianolopError {
+        EsianolopError { kind, span: None, note: None, trace: vec![] }
+    }
+
+    // Donne la position de l'erreur, si elle n'en a pas déjà une
+    pub fn at(mut self, span: Span) -> EsianolopError {
+        self.span.get_or_insert(span);
+        return self;
+    }
+
+    pub fn with_note(mut self, note: String) -> EsianolopError {
+        self.note = Some(note);
+        return self;
+    }
+
+    // Ajoute un appel ou une boucle à la trace (en remontant, donc du plus profond au moins profond)
+    pub fn traced(mut self, entry: TraceEntry) -> EsianolopError {
+        self.trace.push(entry);
+        return self;
+    }
+
+    // Le message de l'erreur, sans sa position ni sa trace
+    pub fn message(&self) -> String {
+        match &self.note {
+            Some(note) => format!("{} {}", self.kind, note),
+            None => self.kind.to_string(),
+        }
+    }
+}
+
+impl From<EsianolopErrorKind> for EsianolopError {
+    fn from(kind: EsianolopErrorKind) -> EsianolopError {
+        EsianolopError::new(kind)
+    }
+}
+
+impl fmt::Display for EsianolopErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EsianolopErrorKind::Overflow(what) => write!(f, "overflow in {}", what),
+            EsianolopErrorKind::NotFinite(what) => write!(f, "not a finite number in {}", what),
+            EsianolopErrorKind::DivisionByZero => write!(f, "division by zero"),
+            EsianolopErrorKind::Negative(what) => write!(f, "negative {}", what),
+            EsianolopErrorKind::InvalidOperand(what) => write!(f, "{}", what),
+            EsianolopErrorKind::NotInteger(what) => write!(f, "non-integer value in {}", what),
+            EsianolopErrorKind::StackUnderflow(what) => write!(f, "not enough values in the stack for {}", what),
+            EsianolopErrorKind::UnknownWord(word) => write!(f, "{} is not a valid expression nor function", word),
+            EsianolopErrorKind::InvalidNumber { text, mode } => write!(f, "{} is not a valid number in {} mode", text, mode),
+            EsianolopErrorKind::Redefinition(name) => write!(f, "trying to define already-defined function {}", name),
+            EsianolopErrorKind::MalformedDefinition(what) => write!(f, "{}", what),
+            EsianolopErrorKind::OutsideLoop(word) => write!(f, "'{}' used outside of {}", word, if word == "j" { "two nested loops" } else { "a loop" }),
+            EsianolopErrorKind::InvalidLoop(what) => write!(f, "{}", what),
+            EsianolopErrorKind::InvalidDirective(what) => write!(f, "{}", what),
+            EsianolopErrorKind::Io(what) => write!(f, "Error while parsing the file: {}", what),
+        }
+    }
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TraceEntry::Call { name, span, defined } => write!(f, "in function {} (defined at {}), called at {}", name, defined, span),
+            TraceEntry::Loop { name, span, iteration } => write!(f, "in {} loop at {}, iteration {}", name, span, iteration),
+        }
+    }
+}
+
+// L'erreur s'affiche comme une backtrace : "Error at ligne:colonne: message", puis une ligne par boucle / appel en cours,
+// du plus profond au moins profond
+impl fmt::Display for EsianolopError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "Error at {}: {}", span, self.message())?,
+            None => write!(f, "{}", self.message())?,
+        }
+        for (i, entry) in self.trace.iter().enumerate() {
+            write!(f, "\n  #{} {}", i, entry)?;
+        }
+        return Ok(());
+    }
+}
+
+impl Error for EsianolopError {}
@@ -0,0 +1,198 @@
+use std::fmt;
+
+// Le lexer découpe le code Esianolop en tokens typés, chacun avec sa position dans le texte.
+// C'est la première étape avant d'executer le code : l'interpréteur ne travaille plus sur des bouts de texte.
+
+// Position d'un token dans le texte, en octets (début inclus, fin exclue), et sa ligne / colonne (en partant de 1)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+// Le coté du stack sur lequel se fait une instruction ("<" pour le devant, ">" pour le fond)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenKind {
+    Number(String),     // Un nombre, tel qu'il est écrit ("-5", "1.5"). Il est lu plus tard, selon le mode de l'interpréteur.
+    Identifier(String), // Un mot : instruction (add, for...) ou nom de fonction, toujours en minuscules
+    Operator(String),   // Une instruction en symboles : + - * / ^ $ ~ < > !
+    Side(Side),         // Le "<" ou ">" collé devant une instruction
+    Colon,              // Le ':' des définitions
+    OpenBlock,          // '['
+    CloseBlock,         // ']'
+    Comment(String),    // Un ';' ou un '#' (compris) et ce qui le suit, jusqu'à la fin de la ligne
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+// Les charactères qui forment les instructions en symboles
+const OPERATOR_CHARS: &str = "+-*/^$~<>!";
+
+// Les charactères qui coupent un mot (en plus des espaces)
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || c == ':' || c == '[' || c == ']' || c == ';' || c == '#'
+}
+
+// Un nombre commence par un chiffre, ou par un signe / un point suivi d'un chiffre ("-5", ".5")
+fn is_number(word: &str) -> bool {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_digit() => true,
+        Some('-') | Some('+') | Some('.') => chars.next().map(|c| c.is_ascii_digit() || c == '.').unwrap_or(false),
+        _ => false,
+    }
+}
+
+// Donne le type d'un mot (sans son "<" / ">" devant)
+fn classify(word: &str) -> TokenKind {
+    if is_number(word) {
+        TokenKind::Number(word.to_ascii_lowercase())
+    } else if word.chars().all(|c| OPERATOR_CHARS.contains(c)) {
+        TokenKind::Operator(word.to_owned())
+    } else {
+        TokenKind::Identifier(word.to_ascii_lowercase())
+    }
+}
+
+// Découpe le texte en tokens (les commentaires compris)
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut chars = text.char_indices().peekable();
+    // La ligne en cours, et la colonne du dernier charactère lu (comptée au fur et à mesure, et pas recomptée pour chaque token)
+    let mut line = 1;
+    let mut column = 0;
+    let span = |start: usize, end: usize, line: usize, column: usize| Span { start, end, line, column };
+
+    while let Some((start, c)) = chars.next() {
+        column += 1;
+        match c {
+            '\n' => {
+                line += 1;
+                column = 0;
+            },
+            c if c.is_whitespace() => (),
+            ':' => tokens.push(Token { kind: TokenKind::Colon, span: span(start, start + 1, line, column) }),
+            '[' => tokens.push(Token { kind: TokenKind::OpenBlock, span: span(start, start + 1, line, column) }),
+            ']' => tokens.push(Token { kind: TokenKind::CloseBlock, span: span(start, start + 1, line, column) }),
+            ';' | '#' => {
+                // Le commentaire va jusqu'à la fin de la ligne
+                let end = text[start..].find('\n').map(|x| start + x).unwrap_or(text.len());
+                tokens.push(Token { kind: TokenKind::Comment(text[start..end].to_owned()), span: span(start, end, line, column) });
+                while chars.peek().map(|(i, _)| *i < end).unwrap_or(false) {
+                    chars.next();
+                    column += 1;
+                }
+            },
+            _ => {
+                // Un mot, jusqu'au prochain séparateur
+                let start_column = column;
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) = chars.peek() {
+                    if is_separator(*c) {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                    column += 1;
+                }
+                let (mut word_start, mut word_column) = (start, start_column);
+                // Un "<" ou ">" devant un mot (d'au moins 2 charactères) choisit le coté du stack
+                if end - start >= 2 && (c == '<' || c == '>') {
+                    let side = if c == '<' { Side::Left } else { Side::Right };
+                    tokens.push(Token { kind: TokenKind::Side(side), span: span(start, start + 1, line, start_column) });
+                    word_start += 1;
+                    word_column += 1;
+                }
+                tokens.push(Token { kind: classify(&text[word_start..end]), span: span(word_start, end, line, word_column) });
+            },
+        }
+    }
+    return tokens;
+}
+
+// Une position s'affiche "ligne:colonne", comme dans les messages d'erreurs
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenKind::Number(a) | TokenKind::Identifier(a) | TokenKind::Operator(a) => write!(f, "{}", a),
+            TokenKind::Side(Side::Left) => write!(f, "<"),
+            TokenKind::Side(Side::Right) => write!(f, ">"),
+            TokenKind::Colon => write!(f, ":"),
+            TokenKind::OpenBlock => write!(f, "["),
+            TokenKind::CloseBlock => write!(f, "]"),
+            TokenKind::Comment(a) => write!(f, "{}", a),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str) -> Vec<TokenKind> {
+        tokenize(text).into_iter().map(|token| token.kind).collect()
+    }
+
+    #[test]
+    fn side_prefixes() {
+        assert_eq!(kinds("<add >+ < >"), vec![
+            TokenKind::Side(Side::Left), TokenKind::Identifier("add".to_owned()),
+            TokenKind::Side(Side::Right), TokenKind::Operator("+".to_owned()),
+            // Seuls, "<" et ">" sont les instructions dpl / dpr
+            TokenKind::Operator("<".to_owned()), TokenKind::Operator(">".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn negative_numbers_and_sub() {
+        assert_eq!(kinds("-5 - 3 -x .5 <-2"), vec![
+            TokenKind::Number("-5".to_owned()), TokenKind::Operator("-".to_owned()),
+            TokenKind::Number("3".to_owned()), TokenKind::Identifier("-x".to_owned()),
+            TokenKind::Number(".5".to_owned()),
+            TokenKind::Side(Side::Left), TokenKind::Number("-2".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn comments_and_definitions() {
+        assert_eq!(kinds("f:[1 ; un\n2]: #!overflow wrap\nF"), vec![
+            TokenKind::Identifier("f".to_owned()), TokenKind::Colon, TokenKind::OpenBlock,
+            TokenKind::Number("1".to_owned()), TokenKind::Comment("; un".to_owned()),
+            TokenKind::Number("2".to_owned()), TokenKind::CloseBlock, TokenKind::Colon,
+            TokenKind::Comment("#!overflow wrap".to_owned()),
+            TokenKind::Identifier("f".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn spans() {
+        let tokens = tokenize("1 >add\n  é #x\n0xFF");
+        let spans = tokens.iter().map(|token| (token.span.start, token.span.end, token.span.line, token.span.column)).collect::<Vec<_>>();
+        assert_eq!(spans, vec![
+            (0, 1, 1, 1), (2, 3, 1, 3), (3, 6, 1, 4),
+            // "é" fait 2 octets mais une seule colonne
+            (9, 11, 2, 3), (12, 14, 2, 5),
+            (15, 19, 3, 1),
+        ]);
+        assert_eq!(tokens[3].kind, TokenKind::Identifier("é".to_owned()));
+        assert_eq!(tokens[5].kind, TokenKind::Number("0xff".to_owned()));
+    }
+}
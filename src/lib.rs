@@ -1,6 +1,16 @@
+// Le code utillise des "return" explicites partout, c'est voulu
+#![allow(clippy::needless_return)]
+
 // 
 // En rust, pour utillsier une librairie, il faut la mettre dans le cargo.toml
 // Comme c'est ma propre libraire, je spécifie les fichiers à importer ici.
 pub mod structs;
 // le pub est pour "publique", donc n'importe qui qui importe ma librairie à accès au code dans le fichier structs 
+pub mod value; // Les valeurs calculées par les arbres, et les modes de calcul
+pub mod bigint; // Les entiers de taille illimitée
+pub mod rational; // Les fractions exactes
 
+pub mod lexer; // Le découpage du code en tokens
+pub mod ast; // L'arbre du programme, construit une seule fois avant l'execution
+pub mod bytecode; // La compilation en bytecode, executé par la machine virtuelle de l'interpréteur
+pub mod error; // Les erreurs de l'interpréteur
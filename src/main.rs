@@ -11,8 +11,10 @@
 // Vous pouvez le faire via la commande `f exemples/1`
 
 
-// J'importe mes classes
-use esianolop;
+// Le code utillise des "return" explicites partout, c'est voulu
+#![allow(clippy::needless_return)]
+
+// Mes classes sont accessibles via esianolop::structs (pas besoin de "use" depuis rust 2018)
 // Importation de env pour les arguments quand utillisé en ligne de commande
 use std::env;
 // Importation de io pour les inputs
@@ -32,7 +34,16 @@ Commandes :
  - print|p         : Affiche les arbres du interpreteur
  - reset|r         : Reset les arbres du interpreteur (pas les fonctions)
  - null|n          : Reset l'interpreteur
+ - mode|m [mode]   : Affiche ou change le type des nombres (int: entiers 64 bits, big: entiers illimités, rational: fractions exactes, float: flottants)
+ - decimals|d [n]  : Affiche les fractions en décimal avec n chiffres après la virgule (sans n: en a/b)
+ - overflow|o [pol]: Affiche ou change ce qu'on fait quand un calcul d'entiers déborde pour les calculs suivants (checked: erreur, wrapping, saturating, mod N: tout modulo N)
+ - disasm <nom>    : Affiche le bytecode compilé de la fonction <nom>
  - quit|q          : Quitte l'invite de commande
+Options (en ligne de commande, avant la commande) :
+ - --mode=<mode>   : Choisis le type des nombres (exemple: `esianolop --mode=big f exemples/1`)
+ - --decimals=<n>  : Affiche les fractions en décimal
+ - --overflow=<pol>: Choisis la politique de débordement (exemple: `esianolop --overflow=mod:1000000007 f exemples/1`)
+Un fichier peut aussi la changer avec la directive `#!overflow <politique>` (par exemple `#!overflow mod 1000000007`) : elle vaut à partir de sa ligne, jusqu'à la fin du fichier
     
 Instructions :
  - add|+ : Ajoute les 2 valeurs
@@ -40,12 +51,32 @@ Instructions :
  - mul|* : Multiplie les 2 valeurs
  - div|/ : Divise les 2 valeurs
  - pow|^ : Met en puissance les 2 valeurs
+ - fdiv  : Divise les 2 valeurs en arrondissant en dessous (-7 2 fdiv => -4, là où div donne -3)
+ - mod   : Modulo, du signe du diviseur (-7 2 mod => 1)
+ - rem   : Reste de la division tronquée, du signe du dividende (-7 2 rem => -1)
+ - divmod: Remplace les 2 valeurs par leur quotient (fdiv) et leur modulo (mod)
  - sqr|$ : Met en racine la valeure
+ - log, exp, sin, cos, tan : Logarithme naturel, exponentielle et trigonométrie (tronqués pour les entiers)
+ - floor, ceil, round      : Arrondis à l'entier en dessous, au dessus, au plus proche
+ - eq, ne, lt, le, gt, ge  : Compare les 2 valeurs (=, !=, <, <=, >, >=), donne 1 si vrai et 0 sinon
+ - and, or, not            : Opérations booléennes (une valeur non nulle est vraie)
+ - gcd, lcm                : Plus grand diviseur commun, plus petit multiple commun des 2 valeurs
+ - min, max, abs           : Le plus petit / le plus grand des 2 valeurs, valeur absolue
+ - fact, choose            : Factorielle (5 fact => 120), coefficient binomial (10 3 choose => 120), jusqu'à 10000!
+ - isprime, nextprime      : 1 si la valeur est un nombre premier (0 sinon), le nombre premier suivant (jusqu'à 512 bits)
+ - modpow                  : Retire 3 valeurs (base exposant modulo) et calcule base^exposant modulo (4 13 497 modpow => 445)
+ - band, bor, bxor, bnot   : Et, ou, ou exclusif, non bit à bit (en complément à deux : sur 64 bits en mode int, sans limite sinon)
+ - shl, shr                : Décale les bits de la valeur à gauche / à droite (1 10 shl => 1024, -7 1 shr => -4)
+ - popcount                : Le nombre de bits à 1 de la valeur
  - dpL|< : Duplique la valeur et la place devant
  - dpR|> : Duplique la valeur et la place derrière
  - dup|~ : Duplique la valeur et la place à coté
  - del|! : Supprimer la valeur
- - <nb>  : Insère un noeu nombre
+ - break, continue : Sort de la boucle en cours, ou passe à son tour suivant (meme depuis une fonction appellée par la boucle)
+ - i, j  : Insère le numéro du tour de la boucle en cours (i), ou de la boucle qui l'entoure (j), comme un nombre
+ - <nb>  : Insère un noeu nombre (signé, par exemple -5. Le "-" collé au nombre n'est pas une soustraction)
+           Il peut s'écrire en hexadécimal (0x1F), binaire (0b1010), octal (0o17), avec des "_" entre les chiffres (1_000_000)
+           ou en notation scientifique (1e6, accepté pour les entiers seulement si la valeur est entière)
 
 Pour chaque instruction vous pouvez spécifiez si elle se fera sur le devant du stack (avec un "<" devant, par défault) ou sur le fond (avec un ">" derrière, par défault juste pour les nombres)
 
@@ -66,46 +97,119 @@ Fonctions :
  - Vous pouvez définir des fonctions en utillisant les ':'. Chaque fonction porte un nom, et est assigné à une série de commandes.
    Par exemple, taper `e test:1 2 +:` définie une fonction "test" qui executera "1 2 +". Pour l'appeller, vous pouvez taper son nom ("e test test" donnera [4 2])
  - Certaine fonction sont pré-définie, par exemple la fonction "for" qui execute son code X fois, X étant la valeur dans le stack.
+ - La fonction "range" retire 3 valeurs (début, fin, pas) et execute son code pour i allant de début (inclus) à fin (exclus), de pas en pas.
+ - La fonction "while" prend deux codes (`while:condition:corps:`) : avant chaque tour elle execute "condition", retire une valeur du stack, et s'arrete si elle est nulle.
+ - La fonction "if" prend deux codes (`if:alors:sinon:`) : elle retire une valeur du stack et execute "alors" si elle est non nulle, "sinon" sinon.
  - Les fonction ne se reset pas avec la commande `reset`, mais seulement avec la commande `null`.
- - Une fonction ne peut etre que défini en une ligne (pas de multiligne possible pour cette version 1.0)
+ - Le code d'une définition peut etre mis entre crochets `nom:[ code ]` : il peut alors contenir d'autres définitions, boucles ou if (ils s'imbriquent).
+ - Une définition peut s'étaler sur plusieurs lignes (avec des commentaires au milieu) : elle se termine au ':' suivant. Voir `exemples/1`.
 Exemples :
  - e one:1: plus:+: 3 one plus => Add(1,3)
  - e 90 5 for:2 +:             => 100 (répéter 5 fois "2 +" depuis 90)
  - e 90 5 <for:2 +:            => 185 (répéter 90 fois "2 +" depuis 5)
  - e t:7:u:+:o:2: t o u        => 9   (7 2 +)
+ - e 3 1 2 >lt if:10 +:10 -:   => 13  (1 < 2, donc on ajoute 10)
+ - e 0 5 <if:1:2:              => 5 2 (on prend la valeur à gauche, 0 est faux)
+ - e 1 while:>~ 100 >lt:2 >*:  => 128 (double 1 tant qu'il est plus petit que 100)
+ - e 0 4 for:i +:              => 6   (0+0+1+2+3)
+ - e 10 0 -3 range:i:          => 10 7 4 1
+ - e 5 for:i continue 100:     => 0 1 2 3 4 (le 100 n'est jamais ajouté)
+ - e 100 for:[ i >~ 5 >eq if:[break][>!] ]   => 5 (s'arrete dès qu'on trouve 5)
+ - e f:[ 0 3 for:[ 2 for:[ i j >* >+ ] ] ] f => 3 (somme des i*j, boucles imbriquées dans une fonction)
+
+Les erreurs indiquent leur position sous la forme ligne:colonne (par exemple "Error at 2:4"),
+suivie de la trace des boucles et appels de fonctions en cours, du plus profond au moins profond :
+   #0 in function g (defined at 1:1), called at 1:30
+   #1 in for loop at 1:23, iteration 2
 "#
 )
 }
 
+// Affiche le résultat de chaque arbre de l'interpréteur, puis les erreurs
+fn print_results(compiler:&esianolop::structs::Esianolop) {
+    let res= compiler.get_result();
+    println!("results = [{}]",res.iter().map(|x| match x {Ok(e) => compiler.format_value(e), Err(_) => "Err".to_string()}).collect::<Vec<String>>().join(", "));
+    println!("{}",res.iter().filter_map(|x| x.as_ref().err()).map(|x| format!("Error: {}",x)).collect::<Vec<String>>().join("\n"));
+}
+
 // Fonction qui execute du code en ligne de commande. Ne retourne rien, affiche directement
-fn execute_command(input:Vec<&str>,mut compiler:&mut esianolop::structs::Esianolop) {
+fn execute_command(input:Vec<&str>,compiler:&mut esianolop::structs::Esianolop) {
 
     // On regarde le permier argument
     match input[0] {
 
         "?" | "help" => help(), // Affichage de l'aide
         "q" | "quit" => process::exit(0), // Quitter l'application
-        "r" | "reset" => compiler.values = Vec::new(), // On reset le stack
+        "r" | "reset" => compiler.values.clear(), // On reset le stack
         "f" | "file" => { // On lit un fichier
             
             // Il y a un double match ici, gloire à rust ^^
             // Le premier retourne l'execution des arguments si ils y sont, sinon retourne une erreur
             match match input.get(1..(input.len())) {
                 Some(e) => {
-                    compiler.parse_file(&e.join(" ")) // le match renvoie l'execution du fichier entré ici
+                    compiler.parse_file(&e.join(" ")).map_err(|e| e.to_string()) // le match renvoie l'execution du fichier entré ici
                 },
                 None => Err("Syntax: f <file_path>".to_owned()) // Si aucun code (None), retourne une erreur
             }  {
                 // Deuxième match, affiche le résultat / l'erreur, que ce soit du premier match ou de l'execution du code
-                Ok(()) => {
-                    let res= compiler.get_result();
-                    println!("results = [{}]",res.iter().map(|x| if x.is_err(){"Err".to_string()}else{x.unwrap().to_string()}).collect::<Vec<String>>().join(", "));
-                    println!("{}",res.iter().filter(|x|x.is_err()).map(|x| format!("Error: {}",x.unwrap_err())).collect::<Vec<String>>().join("\n"));
-                },
+                Ok(()) => print_results(compiler),
                 Err(e) => {println!("{}",e)}
             }
         },
-        "p" | "print" => println!("{:?} => {:?}",compiler.values,compiler.get_result()), // On affiche le stack / le stack compilé
+        "p" | "print" => { // On affiche le stack / le stack compilé (les valeurs comme des nombres)
+            let results = compiler.get_result().iter().map(|x| match x {Ok(e) => format!("Ok({})",compiler.format_value(e)), Err(e) => format!("Err({})",e)}).collect::<Vec<String>>();
+            println!("{:?} => [{}]",compiler.values,results.join(", "))
+        },
+        "m" | "mode" => {
+            // Sans argument on affiche le mode, sinon on le change
+            match input.get(1) {
+                None => println!("Mode: {}",compiler.mode),
+                Some(name) => match esianolop::value::EsianolopMode::from_name(name) {
+                    Some(mode) => {
+                        compiler.mode = mode;
+                        println!("Mode set to {}.",mode);
+                    },
+                    None => println!("Unknow mode '{}'. Modes: int, big, rational, float",name)
+                }
+            }
+        },
+        "d" | "decimals" => {
+            // Sans argument on affiche les fractions telles quelles (a/b), sinon en décimal avec n chiffres
+            match input.get(1).map(|x| x.parse::<usize>()) {
+                None => {
+                    compiler.decimals = None;
+                    println!("Fractions will be shown as a/b.");
+                },
+                Some(Ok(digits)) => {
+                    compiler.decimals = Some(digits);
+                    println!("Fractions will be shown with {} decimals.",digits);
+                },
+                Some(Err(_)) => println!("Syntax: d [number of decimals]")
+            }
+        },
+        "o" | "overflow" => {
+            // Sans argument on affiche la politique, sinon on la change ("o mod 1000000007")
+            match input.get(1..).filter(|x| !x.is_empty()) {
+                None => println!("Overflow: {}",compiler.overflow),
+                Some(words) => match esianolop::value::OverflowPolicy::from_name(&words.join(" ")) {
+                    Some(policy) => {
+                        compiler.overflow = policy;
+                        println!("Overflow set to {}.",policy);
+                    },
+                    None => println!("Unknow overflow policy '{}'. Policies: checked, wrapping, saturating, mod N",words.join(" "))
+                }
+            }
+        },
+        "disasm" => {
+            // On affiche le bytecode de la fonction, tel qu'il est executé par la machine virtuelle
+            match input.get(1) {
+                None => println!("Syntax: disasm <function>"),
+                Some(name) => match compiler.disassemble(&name.to_ascii_lowercase()) {
+                    Some(code) => println!("{}",code),
+                    None => println!("Unknow function '{}'",name)
+                }
+            }
+        },
         "n" | "null" => {
             // Reset tout l'interpreteur (fonctions aussi)
             compiler.clear();
@@ -118,16 +222,12 @@ fn execute_command(input:Vec<&str>,mut compiler:&mut esianolop::structs::Esianol
             match match input.get(1..(input.len())) {
 
                 Some(e) => {
-                    compiler.parse_text(&e.join(" ")) // le match renvoie l'execution du code entré ici
+                    compiler.parse_text(&e.join(" ")).map_err(|e| e.to_string()) // le match renvoie l'execution du code entré ici
                 },
                 None => Err("Syntax: e *[code]".to_owned()) // Si aucun code (None), retourne une erreur
             } {
                 // Deuxième match, affiche le résultat / l'erreur, que ce soit du premier match ou de l'execution du code
-                Ok(()) => {
-                    let res= compiler.get_result();
-                    println!("results = [{}]",res.iter().map(|x| if x.is_err(){"Err".to_string()}else{x.unwrap().to_string()}).collect::<Vec<String>>().join(", "));
-                    println!("{}",res.iter().filter(|x|x.is_err()).map(|x| format!("Error: {}",x.unwrap_err())).collect::<Vec<String>>().join("\n"));
-                },
+                Ok(()) => print_results(compiler),
                 Err(e) => {println!("{}",e)}
             }
 
@@ -140,14 +240,11 @@ fn execute_command(input:Vec<&str>,mut compiler:&mut esianolop::structs::Esianol
 }
 
 // L'invite de commande (ne retourne pas, boucle infinie ou arette le programme)
-pub fn command_line() -> ! {
+pub fn command_line(mut compiler:esianolop::structs::Esianolop) -> ! {
 
     // Affichage du message d'introduction 
     println!("Esianolop v1.0b, par Cyprien Bourotte.\nType 'help' or '?' to get help.");
     
-    // Création d'une instance du interpreteur.
-    let mut compiler = esianolop::structs::Esianolop::new();
-
     loop { // Boucle infinie
 
         // Affichage du curseur
@@ -158,10 +255,10 @@ pub fn command_line() -> ! {
         io::stdin().read_line(&mut input_str).expect("error: unable to read user input");
 
         // Découpe l'input en série d'arguments séparé par un espace (+ trimage d'espaces / tabulation / \r en trop)
-        let mut input: Vec<&str>= input_str.trim().split(" ").filter(|x| x.to_owned().trim() != "" ).collect::<Vec<&str>>();
+        let input: Vec<&str>= input_str.trim().split(" ").filter(|x| x.to_owned().trim() != "" ).collect::<Vec<&str>>();
         
         // Si la commande est vide, on recommence la loop
-        if input.len() == 0 {
+        if input.is_empty() {
             println!("Empty command."); continue;
         }
 
@@ -180,15 +277,40 @@ fn main() {
     // Si il n'y a pas d'arguments (autre que le chemin de l'executable)
     if args.len() <= 1 {
         println!("Starting command-line use, because no arguments has been given");
-        command_line();
+        command_line(esianolop::structs::Esianolop::new());
     }
     
     // On créé un interpreteur
     let mut compiler = esianolop::structs::Esianolop::new();
 
     // Découpe l'input en série d'arguments séparé par un espace (+ trimage d'espaces / tabulation / \r en trop)
-    let input: Vec<&str>= args.get(1..(args.len())).unwrap().iter().filter(|x| x.to_owned().trim() != "" ).map(|x| x as &str).collect::<Vec<&str>>();
+    let mut input: Vec<&str>= args.get(1..(args.len())).unwrap().iter().filter(|x| x.to_owned().trim() != "" ).map(|x| x as &str).collect::<Vec<&str>>();
     
+    // Les options (qui commencent par "--") sont avant la commande
+    while let Some(option) = input.first().filter(|x| x.starts_with("--")).map(|x| x.to_string()) {
+        input.remove(0);
+        match option.split_once('=') {
+            Some(("--mode",name)) => match esianolop::value::EsianolopMode::from_name(name) {
+                Some(mode) => compiler.mode = mode,
+                None => {println!("Unknow mode '{}'. Modes: int, big, rational, float",name);process::exit(1)}
+            },
+            Some(("--decimals",digits)) => match digits.parse::<usize>() {
+                Ok(digits) => compiler.decimals = Some(digits),
+                Err(_) => {println!("Syntax: --decimals=<number of decimals>");process::exit(1)}
+            },
+            Some(("--overflow",name)) => match esianolop::value::OverflowPolicy::from_name(name) {
+                Some(policy) => compiler.overflow = policy,
+                None => {println!("Unknow overflow policy '{}'. Policies: checked, wrapping, saturating, mod:N",name);process::exit(1)}
+            },
+            _ => {println!("Unknow option '{}'. Options: --mode=<mode>, --decimals=<n>, --overflow=<policy>",option);process::exit(1)}
+        }
+    }
+
+    // Si il n'y a que des options, on lance l'invite de commande avec
+    if input.is_empty() {
+        command_line(compiler);
+    }
+
 
     execute_command(input, &mut compiler);
 
@@ -0,0 +1,238 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::bigint::BigInt;
+
+// Fraction exacte numérateur/dénominateur, avec des BigInt pour ne jamais déborder.
+// Elle est toujours normalisée : le dénominateur est positif et la fraction est irréductible (1/3 et non 2/6).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: BigInt,
+    den: BigInt,
+}
+
+impl Rational {
+
+    // Créé une fraction normalisée. Retourne None si le dénominateur est nul.
+    pub fn new(num: BigInt, den: BigInt) -> Option<Rational> {
+        if den.is_zero() {
+            return None;
+        }
+        let gcd = num.gcd(&den);
+        let (mut num, _) = num.div_rem(&gcd)?;
+        let (mut den, _) = den.div_rem(&gcd)?;
+        if den.is_negative() {
+            num = -&num;
+            den = -&den;
+        }
+        return Some(Rational { num, den });
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.num
+    }
+
+    pub fn denominator(&self) -> &BigInt {
+        &self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.num.is_negative()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == BigInt::from(1)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let (num, den) = (self.num.to_f64(), self.den.to_f64());
+        if num.is_finite() && den.is_finite() {
+            return num / den;
+        }
+        // Pour les très grandes fractions on passe par l'écriture décimale (sinon inf/inf)
+        return self.to_decimal_string(20).parse::<f64>().unwrap_or(f64::NAN);
+    }
+
+    pub fn add(&self, other: &Rational) -> Rational {
+        Rational::new(&(&self.num * &other.den) + &(&other.num * &self.den), &self.den * &other.den).unwrap()
+    }
+
+    pub fn sub(&self, other: &Rational) -> Rational {
+        Rational::new(&(&self.num * &other.den) - &(&other.num * &self.den), &self.den * &other.den).unwrap()
+    }
+
+    pub fn mul(&self, other: &Rational) -> Rational {
+        Rational::new(&self.num * &other.num, &self.den * &other.den).unwrap()
+    }
+
+    // Division exacte, None si on divise par zéro
+    pub fn div(&self, other: &Rational) -> Option<Rational> {
+        Rational::new(&self.num * &other.den, &self.den * &other.num)
+    }
+
+    // Puissance entière, l'exposant peut etre négatif (on inverse alors la fraction). None pour 0^-n.
+    pub fn pow(&self, exp: i64) -> Option<Rational> {
+        let abs = u32::try_from(exp.unsigned_abs()).ok()?;
+        let (num, den) = (self.num.pow(abs), self.den.pow(abs));
+        if exp < 0 { Rational::new(den, num) } else { Rational::new(num, den) }
+    }
+
+    // Racine carrée, seulement si elle est exacte (numérateur et dénominateur carrés parfaits)
+    pub fn sqrt(&self) -> Option<Rational> {
+        let num = self.num.sqrt()?;
+        let den = self.den.sqrt()?;
+        if &num * &num != self.num || &den * &den != self.den {
+            return None;
+        }
+        return Rational::new(num, den);
+    }
+
+    // Arrondi à l'entier en dessous
+    pub fn floor(&self) -> BigInt {
+        let (q, r) = self.num.div_rem(&self.den).unwrap();
+        if r.is_negative() { &q - &BigInt::from(1) } else { q }
+    }
+
+    // Arrondi à l'entier au dessus
+    pub fn ceil(&self) -> BigInt {
+        let (q, r) = self.num.div_rem(&self.den).unwrap();
+        if !r.is_zero() && !r.is_negative() { &q + &BigInt::from(1) } else { q }
+    }
+
+    // Arrondi à l'entier le plus proche (les demis s'éloignent de zéro, comme f64::round)
+    pub fn round(&self) -> BigInt {
+        let half = Rational::new(BigInt::from(1), BigInt::from(2)).unwrap();
+        if self.is_negative() {
+            let abs = Rational { num: self.num.abs(), den: self.den.clone() };
+            return -&abs.add(&half).floor();
+        }
+        return self.add(&half).floor();
+    }
+
+    // Affichage décimal arrondi à "digits" chiffres après la virgule
+    pub fn to_decimal_string(&self, digits: usize) -> String {
+        let scale = BigInt::from(10).pow(digits as u32);
+        // On arrondi au plus proche: (2*num*10^n + den) / (2*den), en valeur absolue
+        let two = BigInt::from(2);
+        let scaled = &(&(&two * &self.num.abs()) * &scale) + &self.den;
+        let (rounded, _) = scaled.div_rem(&(&two * &self.den)).unwrap();
+        let (int, frac) = rounded.div_rem(&scale).unwrap();
+        let sign = if self.is_negative() && !rounded.is_zero() { "-" } else { "" };
+        if digits == 0 {
+            return format!("{}{}", sign, int);
+        }
+        return format!("{}{}.{:0>width$}", sign, int, frac.to_string(), width = digits);
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(val: BigInt) -> Rational {
+        Rational { num: val, den: BigInt::from(1) }
+    }
+}
+
+impl FromStr for Rational {
+    type Err = ();
+
+    // Lit un entier ("-12") ou un nombre à virgule ("1.25" devient 5/4), éventuellement en notation scientifique ("1.5e3", "2e-2")
+    fn from_str(text: &str) -> Result<Rational, ()> {
+        if let Some((mantissa, exp)) = text.split_once('e') {
+            let mantissa = mantissa.parse::<Rational>()?;
+            let exp = exp.parse::<i32>().map_err(|_| ())?;
+            // On limite l'exposant, pour ne pas calculer des puissances de 10 gigantesques
+            if exp.unsigned_abs() > 10_000 {
+                return Err(());
+            }
+            let power = Rational::from(BigInt::from(10).pow(exp.unsigned_abs()));
+            return if exp < 0 { mantissa.div(&power).ok_or(()) } else { Ok(mantissa.mul(&power)) };
+        }
+        match text.split_once('.') {
+            None => Ok(Rational::from(text.parse::<BigInt>()?)),
+            Some((int, frac)) => {
+                if frac.is_empty() || !frac.bytes().all(|c| c.is_ascii_digit()) {
+                    return Err(());
+                }
+                let num = format!("{}{}", int, frac).parse::<BigInt>()?;
+                Rational::new(num, BigInt::from(10).pow(frac.len() as u32)).ok_or(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_integer() {
+            return write!(f, "{}", self.num);
+        }
+        return write!(f, "{}/{}", self.num, self.den);
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Rational) -> Ordering {
+        // Les dénominateurs sont positifs, on peut donc comparer les produits en croix
+        (&self.num * &other.den).cmp(&(&other.num * &self.den))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rat(num: i64, den: i64) -> Rational {
+        Rational::new(BigInt::from(num), BigInt::from(den)).unwrap()
+    }
+
+    #[test]
+    fn normalization() {
+        assert_eq!(rat(2, 6).to_string(), "1/3");
+        assert_eq!(rat(3, -6).to_string(), "-1/2");
+        assert_eq!(rat(-4, -2).to_string(), "2");
+        assert_eq!(rat(0, -5).to_string(), "0");
+        assert_eq!(rat(6, 4), rat(3, 2));
+        assert!(Rational::new(BigInt::from(1), BigInt::from(0)).is_none());
+        assert_eq!(rat(1, 6).add(&rat(1, 3)).to_string(), "1/2");
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!("1.25".parse::<Rational>(), Ok(rat(5, 4)));
+        assert_eq!("-0.5".parse::<Rational>(), Ok(rat(-1, 2)));
+        assert_eq!("2e-2".parse::<Rational>(), Ok(rat(1, 50)));
+        assert_eq!("1.5e3".parse::<Rational>(), Ok(rat(1500, 1)));
+        assert!("1.".parse::<Rational>().is_err());
+        assert!("1e10001".parse::<Rational>().is_err());
+    }
+
+    #[test]
+    fn rounding() {
+        let cases = [(7, 2, 3, 4, 4), (-7, 2, -4, -3, -4), (5, 3, 1, 2, 2), (-5, 3, -2, -1, -2), (4, 1, 4, 4, 4), (1, 3, 0, 1, 0)];
+        for &(num, den, floor, ceil, round) in &cases {
+            let a = rat(num, den);
+            assert_eq!((a.floor(), a.ceil(), a.round()), (BigInt::from(floor), BigInt::from(ceil), BigInt::from(round)), "{}", a);
+        }
+    }
+
+    #[test]
+    fn decimal_string() {
+        assert_eq!(rat(1, 3).to_decimal_string(5), "0.33333");
+        assert_eq!(rat(2, 3).to_decimal_string(2), "0.67");
+        assert_eq!(rat(-1, 8).to_decimal_string(2), "-0.13");
+        assert_eq!(rat(1, 200).to_decimal_string(2), "0.01");
+        assert_eq!(rat(7, 2).to_decimal_string(0), "4");
+        // Un négatif arrondi à 0 s'affiche sans "-"
+        assert_eq!(rat(-1, 1000).to_decimal_string(2), "0.00");
+    }
+}
@@ -1,105 +1,520 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::fmt;
 use std::fs;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+
+use crate::ast::{self, Op};
+use crate::bytecode::{self, Function, Opcode};
+use crate::error::{EsianolopError, EsianolopErrorKind, TraceEntry};
+use crate::lexer::{Side, Span};
+use crate::value::{EsianolopMode, EsianolopValue, OverflowPolicy};
 
 // rust fonctionne avec des structures, non pas des classes, mais tout est pareil (sauf que techniquement parlant, tout est statique en rust)
 
 
 // Définition de la liste des instructions (sans les fonctions, ni les for, ils se font à coté)
+// Les fils sont des Rc (compteur de références) : cloner un arbre ne copie que le pointeur, et un meme sous-arbre peut etre partagé par plusieurs noeuds
 #[derive(Clone)]
-#[derive(Debug)]
 pub enum EsianolopInstruction {
     Nul,
-    Add(Box<EsianolopInstruction>,Box<EsianolopInstruction>),
-    Mul(Box<EsianolopInstruction>,Box<EsianolopInstruction>),
-    Sub(Box<EsianolopInstruction>,Box<EsianolopInstruction>),
-    Div(Box<EsianolopInstruction>,Box<EsianolopInstruction>),
-    Pow(Box<EsianolopInstruction>,Box<EsianolopInstruction>),
-    Dup(usize), // Par soucis de performence, les duplications conserve le résultat directement (non pas une référence)
-    DpL(usize), 
-    DpR(usize), 
-    Sqr(Box<EsianolopInstruction>),
-    Num(usize),
+    Add(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Mul(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Sub(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Div(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Pow(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Mod(Rc<EsianolopNode>,Rc<EsianolopNode>), // Modulo (du signe du diviseur)
+    Rem(Rc<EsianolopNode>,Rc<EsianolopNode>), // Reste de la division tronquée (du signe du dividende)
+    FDiv(Rc<EsianolopNode>,Rc<EsianolopNode>), // Division arrondie en dessous
+    Dup(EsianolopValue), // Par soucis de performence, les duplications conserve le résultat directement (non pas une référence)
+    DpL(EsianolopValue), 
+    DpR(EsianolopValue), 
+    Sqr(Rc<EsianolopNode>),
+    Log(Rc<EsianolopNode>), // Logarithme naturel
+    Exp(Rc<EsianolopNode>),
+    Sin(Rc<EsianolopNode>),
+    Cos(Rc<EsianolopNode>),
+    Tan(Rc<EsianolopNode>),
+    Floor(Rc<EsianolopNode>),
+    Ceil(Rc<EsianolopNode>),
+    Round(Rc<EsianolopNode>),
+    Eq(Rc<EsianolopNode>,Rc<EsianolopNode>), // Les comparaisons et opérations booléennes valent 0 ou 1
+    Ne(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Lt(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Le(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Gt(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Ge(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    And(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Or(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Not(Rc<EsianolopNode>),
+    Gcd(Rc<EsianolopNode>,Rc<EsianolopNode>), // Les opérations sur les entiers
+    Lcm(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Abs(Rc<EsianolopNode>),
+    Min(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Max(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Fact(Rc<EsianolopNode>),
+    Choose(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    IsPrime(Rc<EsianolopNode>),
+    NextPrime(Rc<EsianolopNode>),
+    ModPow(Rc<EsianolopNode>,Rc<EsianolopNode>,Rc<EsianolopNode>), // base exposant modulo
+    BAnd(Rc<EsianolopNode>,Rc<EsianolopNode>), // Les opérations sur les bits (en complément à deux)
+    BOr(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    BXor(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    BNot(Rc<EsianolopNode>),
+    Shl(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    Shr(Rc<EsianolopNode>,Rc<EsianolopNode>),
+    PopCount(Rc<EsianolopNode>),
+    Num(EsianolopValue), // Les nombres sont signés, pour que "3 5 -" donne -2 au lieu d'une erreur
+}
+
+// Un noeud de l'arbre : son instruction, et son résultat une fois calculé.
+// Un arbre ne change plus une fois construit, on ne le calcule donc qu'une fois : get_result, les duplications, les for... réutilisent le résultat.
+// Chaque noeud garde la politique de débordement en place quand il a été créé : c'est avec elle qu'il est calculé.
+#[derive(Clone)]
+pub struct EsianolopNode {
+    pub instruction:EsianolopInstruction,
+    pub overflow:OverflowPolicy,
+    cache:RefCell<Option<Result<EsianolopValue,EsianolopError>>>,
+}
+
+// Un noeud "Nul" partagé, qu'on met à la place des fils d'un noeud qu'on détruit (voir Drop)
+thread_local! {
+    static NUL: Rc<EsianolopNode> = Rc::new(EsianolopNode::new(EsianolopInstruction::Nul, OverflowPolicy::Checked));
+}
+
+// Les étapes du calcul d'un arbre, pour execute (qui n'est pas récursive, voir plus bas)
+enum Task<'a> {
+    Eval(&'a EsianolopNode),  // Calculer ce noeud
+    Apply(&'a EsianolopNode), // Ces fils sont calculés, il reste à appliquer l'opération du noeud
+    AndOr(&'a EsianolopNode), // Le premier fil d'un and / or est calculé, on décide si on calcule le deuxième
+    Truth(&'a EsianolopNode), // Le deuxième fil d'un and / or est calculé, le résultat est 0 ou 1
+}
+
+impl EsianolopNode {
+
+    pub fn new(instruction:EsianolopInstruction, overflow:OverflowPolicy) -> EsianolopNode {
+        EsianolopNode { instruction, overflow, cache:RefCell::new(None) }
+    }
+
+    // Garde le résultat du noeud et le retourne
+    fn store(&self, result:Result<EsianolopValue,EsianolopError>) -> Result<EsianolopValue,EsianolopError> {
+        *self.cache.borrow_mut() = Some(result.clone());
+        return result
+    }
+
+    // Le résultat déjà calculé, s'il y en a un
+    fn cached(&self) -> Option<Result<EsianolopValue,EsianolopError>> {
+        return self.cache.borrow().clone()
+    }
+
+    // La fonction execute donne le résultat. Elle calcule les fils avant le noeud, mais sans récursion :
+    // on garde une liste des étapes à faire et des valeurs déjà calculées, pour que meme un arbre très profond
+    // (une boucle qui fait 100000 additions à la suite) ne fasse pas déborder la pile du programme.
+    // Elle est aussi sécurisé. Si une opération rate (exemple: 5/0) elle ne paniquera pas mais arretera le programme
+    pub fn execute(&self) -> Result<EsianolopValue,EsianolopError> {
+        let mut tasks = vec![Task::Eval(self)];
+        let mut results:Vec<EsianolopValue> = vec![];
+
+        while let Some(task) = tasks.pop() {
+            match task {
+                // Si le noeud a déjà été calculé, on reprend son résultat (meme une erreur)
+                Task::Eval(node) if node.cached().is_some() => {
+                    results.push(node.cached().unwrap()?);
+                },
+                Task::Eval(node) => match &node.instruction {
+                    EsianolopInstruction::Nul => results.push(EsianolopValue::Int(0)),
+                    EsianolopInstruction::Dup(a) | EsianolopInstruction::DpL(a) |
+                    EsianolopInstruction::DpR(a) | EsianolopInstruction::Num(a) => results.push(a.clone()),
+                    // "and" et "or" n'évaluent le deuxième arbre que si besoin
+                    EsianolopInstruction::And(a,_) | EsianolopInstruction::Or(a,_) => {
+                        tasks.push(Task::AndOr(node));
+                        tasks.push(Task::Eval(a));
+                    },
+                    _ => {
+                        // Le premier fil est calculé en premier, donc mis en dernier dans les étapes
+                        tasks.push(Task::Apply(node));
+                        tasks.extend(node.instruction.children().iter().rev().flatten().map(|x| Task::Eval(x)));
+                    }
+                },
+                Task::Apply(node) => {
+                    // Les valeurs des fils sont les dernières calculées, dans l'ordre
+                    let args = results.split_off(results.len() - node.instruction.children().iter().flatten().count());
+                    results.push(node.store(node.instruction.apply(&args, node.overflow).map_err(EsianolopError::from))?);
+                },
+                Task::AndOr(node) => {
+                    let a = results.pop().unwrap().is_true();
+                    match (&node.instruction, a) {
+                        (EsianolopInstruction::And(_,_), false) => results.push(node.store(Ok(EsianolopValue::from_bool(false)))?),
+                        (EsianolopInstruction::Or(_,_), true) => results.push(node.store(Ok(EsianolopValue::from_bool(true)))?),
+                        // Sinon le résultat est celui du deuxième arbre
+                        _ => {
+                            tasks.push(Task::Truth(node));
+                            tasks.extend(node.instruction.children()[1].map(|x| Task::Eval(x)));
+                        }
+                    }
+                },
+                Task::Truth(node) => {
+                    let a = results.pop().unwrap();
+                    results.push(node.store(Ok(EsianolopValue::from_bool(a.is_true())))?);
+                }
+            }
+        }
+        return Ok(results.pop().unwrap())
+    }
 }
 
 // ajoute de "méthodes" à l'objet EsianolopInstruction
 impl EsianolopInstruction {
 
-    // La fonction execute donne le résultat. Elle est récursive car elle appelle ces/son fil(s) pour connaitre sa valeur
-    // Elle est aussi sécurisé. Si une opération rate (exemple: 5-8) elle ne paniquera pas mais arretera le programme
-    pub fn execute(&self) -> Result<usize,&str> {
+    // Applique l'opération du noeud à la valeur de ses fils (dans l'ordre, autant que de fils)
+    fn apply(&self, args:&[EsianolopValue], overflow:OverflowPolicy) -> Result<EsianolopValue,EsianolopErrorKind> {
+        let zero = EsianolopValue::Int(0);
+        let (a, b, c) = (&args[0], args.get(1).unwrap_or(&zero), args.get(2).unwrap_or(&zero));
         match self {
-            EsianolopInstruction::Nul => {return Ok(0)},
-            EsianolopInstruction::Add(a,b) => {return a.execute()?.checked_add(b.execute()?).ok_or("overflow in addition")},
-            EsianolopInstruction::Sub(a,b) => {return a.execute()?.checked_sub(b.execute()?).ok_or("negative or overflow in substraction")},
-            EsianolopInstruction::Div(a,b) => {return a.execute()?.checked_div(b.execute()?).ok_or("can't divide")},
-            EsianolopInstruction::Mul(a,b) => {return a.execute()?.checked_mul(b.execute()?).ok_or("overflow in multiplication")},
-            EsianolopInstruction::Pow(a,b) => {return a.execute()?.checked_pow(b.execute()? as u32).ok_or("overflow in powering")},
-            EsianolopInstruction::Dup(a) => {return Ok(*a)},
-            EsianolopInstruction::DpL(a) => {return Ok(*a)},
-            EsianolopInstruction::DpR(a) => {return Ok(*a)},
-            EsianolopInstruction::Sqr(a) => {
-                let res = (a.execute()? as f64).sqrt();
-                if res.is_nan() {return Err("negative square-root")};
-                return Ok(res as usize)
-            },
-            EsianolopInstruction::Num(a) => {return Ok(*a)},
+            EsianolopInstruction::Add(_,_) => {return a.add_with(b, overflow)},
+            EsianolopInstruction::Sub(_,_) => {return a.sub_with(b, overflow)},
+            EsianolopInstruction::Div(_,_) => {return a.div_with(b, overflow)},
+            EsianolopInstruction::Mul(_,_) => {return a.mul_with(b, overflow)},
+            EsianolopInstruction::Pow(_,_) => {return a.pow_with(b, overflow)},
+            EsianolopInstruction::Mod(_,_) => {return a.checked_mod(b).map(|x| overflow.reduce(x))},
+            EsianolopInstruction::Rem(_,_) => {return a.checked_rem(b).map(|x| overflow.reduce(x))},
+            EsianolopInstruction::FDiv(_,_) => {return a.fdiv_with(b, overflow)},
+            EsianolopInstruction::Sqr(_) => {return a.sqrt()},
+            EsianolopInstruction::Log(_) => {return a.apply_f64(f64::ln,"logarithm")},
+            EsianolopInstruction::Exp(_) => {return a.apply_f64(f64::exp,"exponential")},
+            EsianolopInstruction::Sin(_) => {return a.apply_f64(f64::sin,"sinus")},
+            EsianolopInstruction::Cos(_) => {return a.apply_f64(f64::cos,"cosinus")},
+            EsianolopInstruction::Tan(_) => {return a.apply_f64(f64::tan,"tangent")},
+            EsianolopInstruction::Floor(_) => {return Ok(a.floor())},
+            EsianolopInstruction::Ceil(_) => {return Ok(a.ceil())},
+            EsianolopInstruction::Round(_) => {return Ok(a.round())},
+            EsianolopInstruction::Eq(_,_) => {return Ok(EsianolopValue::from_bool(a.compare(b) == Ordering::Equal))},
+            EsianolopInstruction::Ne(_,_) => {return Ok(EsianolopValue::from_bool(a.compare(b) != Ordering::Equal))},
+            EsianolopInstruction::Lt(_,_) => {return Ok(EsianolopValue::from_bool(a.compare(b) == Ordering::Less))},
+            EsianolopInstruction::Le(_,_) => {return Ok(EsianolopValue::from_bool(a.compare(b) != Ordering::Greater))},
+            EsianolopInstruction::Gt(_,_) => {return Ok(EsianolopValue::from_bool(a.compare(b) == Ordering::Greater))},
+            EsianolopInstruction::Ge(_,_) => {return Ok(EsianolopValue::from_bool(a.compare(b) != Ordering::Less))},
+            EsianolopInstruction::And(_,_) => {return Ok(EsianolopValue::from_bool(a.is_true() && b.is_true()))},
+            EsianolopInstruction::Or(_,_) => {return Ok(EsianolopValue::from_bool(a.is_true() || b.is_true()))},
+            EsianolopInstruction::Not(_) => {return Ok(EsianolopValue::from_bool(!a.is_true()))},
+            EsianolopInstruction::Gcd(_,_) => {return a.gcd(b, overflow)},
+            EsianolopInstruction::Lcm(_,_) => {return a.lcm(b, overflow)},
+            EsianolopInstruction::Abs(_) => {return a.abs(overflow)},
+            EsianolopInstruction::Min(_,_) => {return Ok(a.min(b))},
+            EsianolopInstruction::Max(_,_) => {return Ok(a.max(b))},
+            EsianolopInstruction::Fact(_) => {return a.fact(overflow)},
+            EsianolopInstruction::Choose(_,_) => {return a.choose(b, overflow)},
+            EsianolopInstruction::IsPrime(_) => {return a.is_prime()},
+            EsianolopInstruction::NextPrime(_) => {return a.next_prime()},
+            EsianolopInstruction::ModPow(_,_,_) => {return a.modpow(b, c, overflow)},
+            EsianolopInstruction::BAnd(_,_) => {return a.band(b, overflow)},
+            EsianolopInstruction::BOr(_,_) => {return a.bor(b, overflow)},
+            EsianolopInstruction::BXor(_,_) => {return a.bxor(b, overflow)},
+            EsianolopInstruction::BNot(_) => {return a.bnot(overflow)},
+            EsianolopInstruction::Shl(_,_) => {return a.shl(b, overflow)},
+            EsianolopInstruction::Shr(_,_) => {return a.shr(b, overflow)},
+            EsianolopInstruction::PopCount(_) => {return a.popcount()},
+            // Les feuilles n'ont pas d'opération, leur valeur est déjà connue
+            _ => {return Ok(a.clone())},
+        }
+    }
+
+    // Les fils du noeud (de 0 à 3)
+    pub fn children(&self) -> [Option<&Rc<EsianolopNode>>; 3] {
+        match self {
+            EsianolopInstruction::Nul | EsianolopInstruction::Num(_) |
+            EsianolopInstruction::Dup(_) | EsianolopInstruction::DpL(_) | EsianolopInstruction::DpR(_) => [None, None, None],
+            EsianolopInstruction::Sqr(a) | EsianolopInstruction::Log(a) | EsianolopInstruction::Exp(a) |
+            EsianolopInstruction::Sin(a) | EsianolopInstruction::Cos(a) | EsianolopInstruction::Tan(a) |
+            EsianolopInstruction::Floor(a) | EsianolopInstruction::Ceil(a) | EsianolopInstruction::Round(a) |
+            EsianolopInstruction::Not(a) | EsianolopInstruction::Abs(a) | EsianolopInstruction::Fact(a) |
+            EsianolopInstruction::IsPrime(a) | EsianolopInstruction::NextPrime(a) |
+            EsianolopInstruction::BNot(a) | EsianolopInstruction::PopCount(a) => [Some(a), None, None],
+            EsianolopInstruction::Add(a,b) | EsianolopInstruction::Sub(a,b) | EsianolopInstruction::Mul(a,b) |
+            EsianolopInstruction::Div(a,b) | EsianolopInstruction::Pow(a,b) |
+            EsianolopInstruction::Mod(a,b) | EsianolopInstruction::Rem(a,b) | EsianolopInstruction::FDiv(a,b) |
+            EsianolopInstruction::Eq(a,b) | EsianolopInstruction::Ne(a,b) | EsianolopInstruction::Lt(a,b) |
+            EsianolopInstruction::Le(a,b) | EsianolopInstruction::Gt(a,b) | EsianolopInstruction::Ge(a,b) |
+            EsianolopInstruction::And(a,b) | EsianolopInstruction::Or(a,b) |
+            EsianolopInstruction::Gcd(a,b) | EsianolopInstruction::Lcm(a,b) | EsianolopInstruction::Min(a,b) |
+            EsianolopInstruction::Max(a,b) | EsianolopInstruction::Choose(a,b) |
+            EsianolopInstruction::BAnd(a,b) | EsianolopInstruction::BOr(a,b) | EsianolopInstruction::BXor(a,b) |
+            EsianolopInstruction::Shl(a,b) | EsianolopInstruction::Shr(a,b) => [Some(a), Some(b), None],
+            EsianolopInstruction::ModPow(a,b,c) => [Some(a), Some(b), Some(c)],
+        }
+    }
+
+    // Pareil que children, mais modifiables (pour Drop)
+    fn children_mut(&mut self) -> [Option<&mut Rc<EsianolopNode>>; 3] {
+        match self {
+            EsianolopInstruction::Nul | EsianolopInstruction::Num(_) |
+            EsianolopInstruction::Dup(_) | EsianolopInstruction::DpL(_) | EsianolopInstruction::DpR(_) => [None, None, None],
+            EsianolopInstruction::Sqr(a) | EsianolopInstruction::Log(a) | EsianolopInstruction::Exp(a) |
+            EsianolopInstruction::Sin(a) | EsianolopInstruction::Cos(a) | EsianolopInstruction::Tan(a) |
+            EsianolopInstruction::Floor(a) | EsianolopInstruction::Ceil(a) | EsianolopInstruction::Round(a) |
+            EsianolopInstruction::Not(a) | EsianolopInstruction::Abs(a) | EsianolopInstruction::Fact(a) |
+            EsianolopInstruction::IsPrime(a) | EsianolopInstruction::NextPrime(a) |
+            EsianolopInstruction::BNot(a) | EsianolopInstruction::PopCount(a) => [Some(a), None, None],
+            EsianolopInstruction::Add(a,b) | EsianolopInstruction::Sub(a,b) | EsianolopInstruction::Mul(a,b) |
+            EsianolopInstruction::Div(a,b) | EsianolopInstruction::Pow(a,b) |
+            EsianolopInstruction::Mod(a,b) | EsianolopInstruction::Rem(a,b) | EsianolopInstruction::FDiv(a,b) |
+            EsianolopInstruction::Eq(a,b) | EsianolopInstruction::Ne(a,b) | EsianolopInstruction::Lt(a,b) |
+            EsianolopInstruction::Le(a,b) | EsianolopInstruction::Gt(a,b) | EsianolopInstruction::Ge(a,b) |
+            EsianolopInstruction::And(a,b) | EsianolopInstruction::Or(a,b) |
+            EsianolopInstruction::Gcd(a,b) | EsianolopInstruction::Lcm(a,b) | EsianolopInstruction::Min(a,b) |
+            EsianolopInstruction::Max(a,b) | EsianolopInstruction::Choose(a,b) |
+            EsianolopInstruction::BAnd(a,b) | EsianolopInstruction::BOr(a,b) | EsianolopInstruction::BXor(a,b) |
+            EsianolopInstruction::Shl(a,b) | EsianolopInstruction::Shr(a,b) => [Some(a), Some(b), None],
+            EsianolopInstruction::ModPow(a,b,c) => [Some(a), Some(b), Some(c)],
         }
     }
+
+    // Le nom du noeud à l'affichage
+    fn name(&self) -> &'static str {
+        match self {
+            EsianolopInstruction::Nul => "Nul",
+            EsianolopInstruction::Add(_,_) => "Add",
+            EsianolopInstruction::Sub(_,_) => "Sub",
+            EsianolopInstruction::Mul(_,_) => "Mul",
+            EsianolopInstruction::Div(_,_) => "Div",
+            EsianolopInstruction::Pow(_,_) => "Pow",
+            EsianolopInstruction::Mod(_,_) => "Mod",
+            EsianolopInstruction::Rem(_,_) => "Rem",
+            EsianolopInstruction::FDiv(_,_) => "FDiv",
+            EsianolopInstruction::Dup(_) => "Dup",
+            EsianolopInstruction::DpL(_) => "DpL",
+            EsianolopInstruction::DpR(_) => "DpR",
+            EsianolopInstruction::Sqr(_) => "Sqr",
+            EsianolopInstruction::Log(_) => "Log",
+            EsianolopInstruction::Exp(_) => "Exp",
+            EsianolopInstruction::Sin(_) => "Sin",
+            EsianolopInstruction::Cos(_) => "Cos",
+            EsianolopInstruction::Tan(_) => "Tan",
+            EsianolopInstruction::Floor(_) => "Floor",
+            EsianolopInstruction::Ceil(_) => "Ceil",
+            EsianolopInstruction::Round(_) => "Round",
+            EsianolopInstruction::Eq(_,_) => "Eq",
+            EsianolopInstruction::Ne(_,_) => "Ne",
+            EsianolopInstruction::Lt(_,_) => "Lt",
+            EsianolopInstruction::Le(_,_) => "Le",
+            EsianolopInstruction::Gt(_,_) => "Gt",
+            EsianolopInstruction::Ge(_,_) => "Ge",
+            EsianolopInstruction::And(_,_) => "And",
+            EsianolopInstruction::Or(_,_) => "Or",
+            EsianolopInstruction::Not(_) => "Not",
+            EsianolopInstruction::Gcd(_,_) => "Gcd",
+            EsianolopInstruction::Lcm(_,_) => "Lcm",
+            EsianolopInstruction::Abs(_) => "Abs",
+            EsianolopInstruction::Min(_,_) => "Min",
+            EsianolopInstruction::Max(_,_) => "Max",
+            EsianolopInstruction::Fact(_) => "Fact",
+            EsianolopInstruction::Choose(_,_) => "Choose",
+            EsianolopInstruction::IsPrime(_) => "IsPrime",
+            EsianolopInstruction::NextPrime(_) => "NextPrime",
+            EsianolopInstruction::ModPow(_,_,_) => "ModPow",
+            EsianolopInstruction::BAnd(_,_) => "BAnd",
+            EsianolopInstruction::BOr(_,_) => "BOr",
+            EsianolopInstruction::BXor(_,_) => "BXor",
+            EsianolopInstruction::BNot(_) => "BNot",
+            EsianolopInstruction::Shl(_,_) => "Shl",
+            EsianolopInstruction::Shr(_,_) => "Shr",
+            EsianolopInstruction::PopCount(_) => "PopCount",
+            EsianolopInstruction::Num(_) => "Num",
+        }
+    }
+
+    // Affiche l'arbre XXX(fil1,fil2 ...) sans récursion, comme execute. "debug" sépare les fils par ", " au lieu de ","
+    fn write_tree(&self, f:&mut fmt::Formatter, debug:bool) -> fmt::Result {
+        // Les étapes : soit un noeud à afficher, soit du texte (les ',' et ')' entre les fils)
+        let mut tasks:Vec<Result<&EsianolopInstruction,&str>> = vec![Ok(self)];
+        let separator = if debug {", "} else {","};
+
+        while let Some(task) = tasks.pop() {
+            let node = match task {
+                Ok(e) => e,
+                Err(text) => {write!(f,"{}",text)?; continue}
+            };
+            match node {
+                EsianolopInstruction::Nul => write!(f,"Nul")?,
+                EsianolopInstruction::Dup(a) | EsianolopInstruction::DpL(a) |
+                EsianolopInstruction::DpR(a) | EsianolopInstruction::Num(a) => {
+                    // La valeur s'affiche comme un nombre (5, 3/2...), meme en Debug
+                    write!(f,"{}({})",node.name(),a)?
+                },
+                _ => {
+                    write!(f,"{}(",node.name())?;
+                    tasks.push(Err(")"));
+                    // Les fils à l'envers (le premier affiché est le dernier ajouté), séparés par des ','
+                    for (i, child) in node.children().iter().enumerate().rev().filter_map(|(i, x)| x.map(|x| (i, x))) {
+                        tasks.push(Ok(&child.instruction));
+                        if i > 0 {
+                            tasks.push(Err(separator));
+                        }
+                    }
+                }
+            }
+        }
+        return Ok(())
+    }
 }
 
 // Implémentation du charactère affichage pour une instruction (similaire à classe.__str__)
-// La fonction est récursive car lors de l'affichage, il demande à afficher le(s) fil(s) au noeu.
+// J'affiche XXX(valeur1, valeur2 ...) selon mon type, et mes fils à l'intérieur
 impl fmt::Display for EsianolopInstruction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        
-        match self { // Selon mon type, j'affiche XXX(valeur1, valeur2 ...)
-            EsianolopInstruction::Nul => {write!(f,"Nul")},
-            EsianolopInstruction::Add(a,b) => {return write!(f,"Add({},{})",a,b)},
-            EsianolopInstruction::Sub(a,b) => {return write!(f,"Sub({},{})",a,b)},
-            EsianolopInstruction::Mul(a,b) => {return write!(f,"Mul({},{})",a,b)},
-            EsianolopInstruction::Div(a,b) => {return write!(f,"Div({},{})",a,b)},
-            EsianolopInstruction::Pow(a,b) => {return write!(f,"Pow({},{})",a,b)},
-            EsianolopInstruction::Dup(a) => {return write!(f,"Dup({})",a)},
-            EsianolopInstruction::DpL(a) => {return write!(f,"DpL({})",a)},
-            EsianolopInstruction::DpR(a) => {return write!(f,"DpR({})",a)},
-            EsianolopInstruction::Sqr(a) => {return write!(f,"Sqr({})",a)},
-            EsianolopInstruction::Num(a) => {return write!(f,"Num({})",a)},
+        return self.write_tree(f, false)
+    }
+}
+
+impl fmt::Debug for EsianolopInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return self.write_tree(f, true)
+    }
+}
+
+// Un noeud s'affiche comme son instruction
+impl fmt::Display for EsianolopNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return self.instruction.write_tree(f, false)
+    }
+}
+
+impl fmt::Debug for EsianolopNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return self.instruction.write_tree(f, true)
+    }
+}
+
+// La destruction d'un arbre est elle aussi faite sans récursion : par défault, rust détruirait les fils dans les fils...
+// On détache les fils dont on est le seul propriétaire (en mettant le noeud Nul partagé à leur place) et on les détruits un par un.
+impl Drop for EsianolopInstruction {
+    fn drop(&mut self) {
+        let mut nodes = vec![];
+        for child in self.children_mut().iter_mut().flatten() {
+            if Rc::strong_count(child) == 1 {
+                nodes.push(std::mem::replace(&mut **child, NUL.with(Rc::clone)));
+            }
+        }
+        while let Some(node) = nodes.pop() {
+            if let Ok(mut node) = Rc::try_unwrap(node) {
+                for child in node.instruction.children_mut().iter_mut().flatten() {
+                    if Rc::strong_count(child) == 1 {
+                        nodes.push(std::mem::replace(&mut **child, NUL.with(Rc::clone)));
+                    }
+                }
+                // node est détruit ici, mais ses fils sont le noeud Nul : ça ne va pas plus loin
+            }
         }
     }
 }
 
 
+// Erreur quand le stack n'a pas assez de valeurs pour "what"
+fn underflow(what:String) -> EsianolopError {
+    EsianolopErrorKind::StackUnderflow(what).into()
+}
+
+// Les sorties de boucle demandées par "break" et "continue". Elles remontent les appels de fonctions jusqu'à la boucle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LoopControl {
+    Break,
+    Continue,
+}
+
+// Une boucle en cours dans la machine virtuelle
+// avec  kind : Le type de boucle et où elle en est
+// et    body : La première instruction du corps (ou de la condition pour le while)
+// et     end : L'instruction juste après la boucle
+// et iteration : Le numéro du tour en cours (en partant de 1), pour la trace des erreurs
+struct LoopRecord {
+    kind:LoopKind,
+    body:usize,
+    end:usize,
+    span:Span,
+    iteration:usize,
+}
+
+enum LoopKind {
+    For { index:i64, count:i64 },
+    Range { index:EsianolopValue, end:EsianolopValue, step:EsianolopValue, direction:Ordering },
+    While { index:i64 },
+}
+
+// Un appel de fonction en cours : son bytecode, la prochaine instruction, l'appel (None pour le programme) et ses boucles en cours
+struct Frame {
+    bytecode:Rc<Vec<Opcode>>,
+    pc:usize,
+    call:Option<CallRecord>,
+    loops:Vec<LoopRecord>,
+}
+
+// avec    name : Le nom de la fonction appellée
+// et      span : L'endroit de l'appel
+// et   defined : L'endroit où la fonction est définie
+struct CallRecord {
+    name:String,
+    span:Span,
+    defined:Span,
+}
+
 // Definition de la structure pour le interpréteur
-// avec    values : Stack d'Arbres 
-// et   fonctions : Des bouts de codes stoqué sous des Strings executes dès que appelé. Un dictionnaire au final.
+// avec    values : Stack d'Arbres, à double entrée : ajouter / retirer au devant est aussi rapide que derrière
+// et   fonctions : Des bouts de codes compilés en bytecode, executes dès que appelé. Un dictionnaire au final.
+// et        mode : Le type des nombres écrits dans le code (i64, entiers illimités, fractions...)
+// et    decimals : Si défini, les fractions sont affichées en décimal avec ce nombre de chiffres après la virgule
+// et    overflow : Ce qu'on fait quand un calcul déborde (erreur, débordement, saturation ou modulo N)
+// et loop_indices : Le numéro du tour de chaque boucle en cours (la plus profonde à la fin), pour les instructions "i" et "j"
+// et      control : Un "break" ou "continue" en cours, qui arrete le code jusqu'à la boucle la plus proche
 pub struct Esianolop {
-    pub values:Vec<EsianolopInstruction>,
-    pub functions:HashMap::<String,String>,
+    pub values:VecDeque<EsianolopNode>,
+    pub functions:HashMap::<String,Function>,
+    pub mode:EsianolopMode,
+    pub decimals:Option<usize>,
+    pub overflow:OverflowPolicy,
+    loop_indices:Vec<EsianolopValue>,
+    control:Option<LoopControl>,
 }
 
 
+// Un interpréteur par défault est un interpréteur vide
+impl Default for Esianolop {
+    fn default() -> Self {
+        Esianolop::new()
+    }
+}
+
 // Implémentation de "méthodes" -en rust c'est des fonction car tout est statique- pour l'objet Esianolop
 impl Esianolop {
 
     // méthode new: retourne une nouvelle instance vide de Esianolop
     pub fn new() -> Esianolop {
+        Esianolop::with_mode(EsianolopMode::Int)
+    }
+
+    // méthode with_mode: pareil que new, mais en choisissant le type des nombres
+    pub fn with_mode(mode:EsianolopMode) -> Esianolop {
         Esianolop {
-            values:vec![],
-            functions:HashMap::new()
+            values:VecDeque::new(),
+            functions:HashMap::new(),
+            mode,
+            decimals:None,
+            overflow:OverflowPolicy::Checked,
+            loop_indices:vec![],
+            control:None
         }
     }
 
-    // reset les valeures
+    // reset les valeures (le mode est conservé)
     pub fn clear(&mut self) {
         self.values.clear();
         self.functions.clear();
     }
 
-    // Execute un fichier. Retourne soit Err(message d'erreur) ou Ok(())
-    pub fn parse_file(&mut self,filename:&str) -> Result<(),String> {
+    // Execute un fichier. Retourne soit Err(erreur) ou Ok(())
+    pub fn parse_file(&mut self,filename:&str) -> Result<(),EsianolopError> {
 
-        match fs::read_to_string(filename.to_owned()) { // Est-ce que le ficher à pu etre lu ?
+        match fs::read_to_string(filename) { // Est-ce que le ficher à pu etre lu ?
 
             // Si oui, appelle de self.parse_text() avec le contenu du fichier et on retourne (car self.parse_text() à la même signiature)
             Ok(e) => {
@@ -107,311 +522,493 @@ impl Esianolop {
                 return self.parse_text(&e)
             }, 
             // Si non, on retourne une erreur
-            Err(e)=> {return Err(format!("Error while parsing the file: {}",e))}, 
+            Err(e)=> {return Err(EsianolopErrorKind::Io(e.to_string()).into())}, 
         }
     }
 
 
     // retourne le stack avec toutes les arbres calculés.
-    pub fn get_result(&self) -> Vec<Result<usize,&str>> {
-        return self.values.iter().map(|x| x.execute()).collect::<Vec<Result<usize,&str>>>()
+    pub fn get_result(&self) -> Vec<Result<EsianolopValue,EsianolopError>> {
+        return self.values.iter().map(|x| x.execute()).collect::<Vec<Result<EsianolopValue,EsianolopError>>>()
     }
 
-    fn execute_instruction(&mut self, vec_from_down:bool,specified:bool, mut instruction:&str) -> Result<(),String> {
+    // Affiche une valeur selon les réglages de l'interpréteur (fraction "a/b" ou approximation décimale)
+    pub fn format_value(&self, value:&EsianolopValue) -> String {
+        return value.to_string_with(self.decimals)
+    }
 
-        instruction = instruction.trim();
+    // Execute une instruction de base sur le stack
+    fn execute_instruction(&mut self, vec_from_down:bool,specified:bool, instruction:Op) -> Result<(),EsianolopError> {
 
-        match instruction.trim() {
+        match instruction {
             // ----- les opérations qui prennent 2 entrées dans le stack -----
-            "+" | "add" |
-            "-" | "sub" |
-            "*" | "mul" |
-            "/" | "div" |
-            "^" | "pow" => {
-
-
-                // On obtiens la classe correspondante à notre instruction
-                let operation_fn = match instruction {
-                    "+" | "add" => EsianolopInstruction::Add,
-                    "-" | "sub" => EsianolopInstruction::Sub,
-                    "*" | "mul" => EsianolopInstruction::Mul,
-                    "/" | "div" => EsianolopInstruction::Div,
-                    "^" | "pow" => EsianolopInstruction::Pow,
-                    e           => {println!("What ???:{:?}",e);unreachable!()} // Ne devrai jamais arriver, mais si oui, panique le programme (arret brutal)
+            Op::Add => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Add),
+            Op::Sub => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Sub),
+            Op::Mul => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Mul),
+            Op::Div => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Div),
+            Op::Pow => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Pow),
+            Op::Mod => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Mod),
+            Op::Rem => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Rem),
+            Op::FDiv => self.push_binary(vec_from_down, instruction, EsianolopInstruction::FDiv),
+            Op::Eq  => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Eq),
+            Op::Ne  => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Ne),
+            Op::Lt  => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Lt),
+            Op::Le  => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Le),
+            Op::Gt  => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Gt),
+            Op::Ge  => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Ge),
+            Op::And => self.push_binary(vec_from_down, instruction, EsianolopInstruction::And),
+            Op::Or  => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Or),
+            Op::Gcd => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Gcd),
+            Op::Lcm => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Lcm),
+            Op::Min => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Min),
+            Op::Max => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Max),
+            Op::Choose => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Choose),
+            Op::BAnd => self.push_binary(vec_from_down, instruction, EsianolopInstruction::BAnd),
+            Op::BOr => self.push_binary(vec_from_down, instruction, EsianolopInstruction::BOr),
+            Op::BXor => self.push_binary(vec_from_down, instruction, EsianolopInstruction::BXor),
+            Op::Shl => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Shl),
+            Op::Shr => self.push_binary(vec_from_down, instruction, EsianolopInstruction::Shr),
+
+
+            // ----- modpow prend 3 entrées : base exposant modulo -----
+            Op::ModPow => {
+
+                if self.values.len() < 3 {
+                    return Err(underflow(format!("'{}' to {} (3 values needed)",instruction,if vec_from_down {"left"} else {"right"})))
+                }
+
+                // Comme pour les opérations à 2 entrées, les valeurs restent dans l'ordre du stack
+                if vec_from_down {
+                    let (a, b, c) = (self.values.pop_front().unwrap(), self.values.pop_front().unwrap(), self.values.pop_front().unwrap());
+                    self.values.push_front(EsianolopNode::new(EsianolopInstruction::ModPow(Rc::new(a), Rc::new(b), Rc::new(c)), self.overflow));
+                } else {
+                    let (c, b, a) = (self.values.pop_back().unwrap(), self.values.pop_back().unwrap(), self.values.pop_back().unwrap());
+                    self.values.push_back(EsianolopNode::new(EsianolopInstruction::ModPow(Rc::new(a), Rc::new(b), Rc::new(c)), self.overflow));
                 };
-                
-                // Obtenir les 2 premières valeures du stack / deux dernières
-                let vals = if vec_from_down {
-                        match self.values.get(0..2) {
-                            Some(e) => e,
-                            None => return Err(format!("not enogth values in buffer to left '{}'",instruction))
-                        }
-                    } else {
-                        match self.values.get((self.values.len()-2)..(self.values.len())) {
-                            Some(e) => e,
-                            None => return Err(format!("not enogth values in buffer to right '{}'",instruction))
-                        }
-                    };
-                
-                // Instancier avec les deux valeures
-                let operation = operation_fn(Box::new(vals[0].to_owned()), Box::new(vals[1].to_owned()));
 
-                // Mettre dans le stack
+                Ok(())
+            },
+
+
+            // ----- divmod : le quotient (arrondi en dessous) et le modulo -----
+            Op::DivMod => {
+
+                if self.values.len() < 2 {
+                    return Err(underflow(format!("'{}' to {}",instruction,if vec_from_down {"left"} else {"right"})))
+                }
+
+                // Les deux arbres partagent les 2 valeures (des Rc), elles ne seront calculées qu'une fois.
+                // Le quotient est placé avant le modulo, dans l'ordre du stack
                 if vec_from_down {
-                    self.values.drain(1..2);
-                    self.values[0] = operation;
+                    let (a, b) = (Rc::new(self.values.pop_front().unwrap()), Rc::new(self.values.pop_front().unwrap()));
+                    self.values.push_front(EsianolopNode::new(EsianolopInstruction::Mod(Rc::clone(&a), Rc::clone(&b)), self.overflow));
+                    self.values.push_front(EsianolopNode::new(EsianolopInstruction::FDiv(a, b), self.overflow));
                 } else {
-                    self.values.drain((self.values.len()-2)..(self.values.len()));
-                    self.values.push(operation);
+                    let (b, a) = (Rc::new(self.values.pop_back().unwrap()), Rc::new(self.values.pop_back().unwrap()));
+                    self.values.push_back(EsianolopNode::new(EsianolopInstruction::FDiv(Rc::clone(&a), Rc::clone(&b)), self.overflow));
+                    self.values.push_back(EsianolopNode::new(EsianolopInstruction::Mod(a, b), self.overflow));
                 };
 
-                Ok(()) // Tout est bon, on retourne Ok(()) !
+                Ok(())
             },
 
 
             // ----- The duplicate instructions -----
-            "~" | "dup" |
-            "<" | "dpl" |
-            ">" | "dpr" => {
+            Op::Dup | Op::DpL | Op::DpR => {
 
                 // Tout les cas ou la destination est à push sur la pile
-                let right_is_destination:bool = (instruction == "dpr") | (instruction ==  ">") | ((instruction == "~") & (!vec_from_down)); 
+                let right_is_destination:bool = (instruction == Op::DpR) | ((instruction == Op::Dup) & (!vec_from_down)); 
                 //println!("Is right destination ? {}",right_is_destination); // Tempory debug
 
                 let val = if vec_from_down {
-                        let temp = self.values.get(0);
+                        let temp = self.values.front();
                         match temp {
                             Some(e) => e,
-                            None => return Err(format!("no value in buffer to duplicate left to {}", if right_is_destination {"right"} else {"left"}))
+                            None => return Err(underflow(format!("duplicate left to {}", if right_is_destination {"right"} else {"left"})))
                         }
                     } else {
-                        let temp = self.values.get(self.values.len()-1);
+                        let temp = self.values.back();
                         match temp {
                             Some(e) => e,
-                            None => return Err(format!("no value in buffer to duplicate right to {}",if right_is_destination {"right"} else {"left"}))
+                            None => return Err(underflow(format!("duplicate right to {}",if right_is_destination {"right"} else {"left"})))
                         }
                     };
                  
-                let val = match val.clone().execute() {
+                let val = match val.execute() {
                     Ok(e) => e,
-                    Err(e) => return Err(format!("{} in duplicate {} to {}",e,if vec_from_down {"left"} else {"right"},if right_is_destination {"right"} else {"left"}))
+                    Err(e) => return Err(e.with_note(format!("in duplicate {} to {}",if vec_from_down {"left"} else {"right"},if right_is_destination {"right"} else {"left"})))
                 };
                 if right_is_destination {
                     // push back
-                    self.values.push(EsianolopInstruction::Dup(val));
+                    self.values.push_back(EsianolopNode::new(EsianolopInstruction::Dup(val), self.overflow));
                 } else {
                     // push front
-                    let mut tmp = Vec::new();
-                    tmp.push(EsianolopInstruction::Dup(val));
-                    tmp.extend(self.values.to_owned());
-                    self.values = tmp;
+                    self.values.push_front(EsianolopNode::new(EsianolopInstruction::Dup(val), self.overflow));
                 };
 
                 Ok(()) // Tout est bon, on retourne Ok(()) !
             },
             // ----- Les opérations qui prennent 1 entrée -----
-            "$" | "sqr" => {
-                match vec_from_down { // Si on prend la valeure depuis la guache ou droite 
-                    false => {
-                        let temp = self.values.get(self.values.len()-1);
-                        let val = match temp {
-                            Some(e) => e,
-                            None => return Err("no value in buffer to take the left square root".to_owned())
-                        };
-                        self.values.push(EsianolopInstruction::Sqr(Box::new(val.clone())));
-                        self.values.drain((self.values.len()-2)..(self.values.len()-1));
-                    },
-                    true => {
-                        let temp = self.values.get(0);
-                        let val = match temp {
-                            Some(e) => e,
-                            None => return Err("no value in buffer to take the right square root".to_owned())
-                        };
-                        self.values[0] = EsianolopInstruction::Sqr(Box::new(val.clone()));
-                    }
+            Op::Sqr   => self.push_unary(vec_from_down, instruction, EsianolopInstruction::Sqr),
+            Op::Log   => self.push_unary(vec_from_down, instruction, EsianolopInstruction::Log),
+            Op::Exp   => self.push_unary(vec_from_down, instruction, EsianolopInstruction::Exp),
+            Op::Sin   => self.push_unary(vec_from_down, instruction, EsianolopInstruction::Sin),
+            Op::Cos   => self.push_unary(vec_from_down, instruction, EsianolopInstruction::Cos),
+            Op::Tan   => self.push_unary(vec_from_down, instruction, EsianolopInstruction::Tan),
+            Op::Floor => self.push_unary(vec_from_down, instruction, EsianolopInstruction::Floor),
+            Op::Ceil  => self.push_unary(vec_from_down, instruction, EsianolopInstruction::Ceil),
+            Op::Round => self.push_unary(vec_from_down, instruction, EsianolopInstruction::Round),
+            Op::Not   => self.push_unary(vec_from_down, instruction, EsianolopInstruction::Not),
+            Op::Abs   => self.push_unary(vec_from_down, instruction, EsianolopInstruction::Abs),
+            Op::Fact  => self.push_unary(vec_from_down, instruction, EsianolopInstruction::Fact),
+            Op::IsPrime => self.push_unary(vec_from_down, instruction, EsianolopInstruction::IsPrime),
+            Op::NextPrime => self.push_unary(vec_from_down, instruction, EsianolopInstruction::NextPrime),
+            Op::BNot  => self.push_unary(vec_from_down, instruction, EsianolopInstruction::BNot),
+            Op::PopCount => self.push_unary(vec_from_down, instruction, EsianolopInstruction::PopCount),
+            // ----- Les numéros de tour des boucles -----
+            Op::I | Op::J => {
+                // "i" est le tour de la boucle la plus proche, "j" celui de la boucle qui l'entoure
+                let depth = if instruction == Op::I {1} else {2};
+                let index = match self.loop_indices.len().checked_sub(depth).map(|x| self.loop_indices[x].clone()) {
+                    Some(e) => e,
+                    None => return Err(EsianolopErrorKind::OutsideLoop(instruction.to_string()).into())
                 };
+                // Comme pour les nombres, par défault on l'ajoute à droite
+                self.push_number(specified & vec_from_down, index);
                 Ok(())
-            }
+            },
+            // ----- Sorties de boucle -----
+            Op::Break | Op::Continue => {
+                if self.loop_indices.is_empty() {
+                    return Err(EsianolopErrorKind::OutsideLoop(instruction.to_string()).into())
+                }
+                // On ne fait que le noter : l'execution s'arrete dès qu'elle le voit, jusqu'à la boucle
+                self.control = Some(if instruction == Op::Break {LoopControl::Break} else {LoopControl::Continue});
+                Ok(())
+            },
             // ----- Delete -----
-            "!" | "del" => {
-                if self.values.len() == 0 { // Si aucune valeure dans le stack d'arbre
-                    return Err("no value to remove".to_owned()); // Erreur
+            Op::Del => {
+                if self.values.is_empty() { // Si aucune valeure dans le stack d'arbre
+                    return Err(underflow("'del'".to_owned())); // Erreur
                 }
                 match vec_from_down { // Sinon, retirer la valeur correspondante
-                    false => self.values.remove(self.values.len()-1),
-                    true => self.values.remove(0),
+                    false => self.values.pop_back(),
+                    true => self.values.pop_front(),
                 };
                 Ok(())
             }
-                // ----- Le reste (fonctions, nombre, non-définie) -----
-            ins => {
-                match ins.to_owned().parse::<usize>() {
-                    Ok(e) => {
-
-                        match (specified,vec_from_down) { // Par défault, la position d'un nombre est à droite, et pas à gauche, donc on vérifie si la position à été définie
-                            (true,true) => { // Si position définie ET à gauche
-                                let mut tmp = Vec::new();
-                                tmp.push(EsianolopInstruction::Num(e as usize));
-                                tmp.extend(self.values.to_owned());
-                                self.values = tmp;
-                            },
-                            (false,_) | (true,false) => { // si position pas définie OU position définie et droite
-                                self.values.push(EsianolopInstruction::Num(e as usize))
-                            }
-                        }
-                    },
-                    Err(_) => {
-                        
-                        if self.functions.contains_key(ins) { // Si c'est dans la liste des fonctions
-                            let x = &self.functions.get(ins).unwrap().clone(); // On prend le code défini par la fonction
-                            println!("Executing function {} with {}",ins,x);
-                            return match self.parse_text(x) { // Execute le code de la fonction (marche pour les fonctions récursive donc)
-                                Err(e) => Err(e+" in function "+ins), // Ajout à l'erreur des informations de la trace
-                                Ok(_) => Ok(())
-                            } 
-                        } else {
-                            return Err(format!("{} is not a valid expression nor function",ins)) // Sinon on retourne une erreur
-                        }
-                        
-                    }
-                };
-                Ok(()) // Tout vas bien !
-            }
-        } 
+        }
     }
-    
 
+    // Retire 2 valeurs du stack (à gauche si vec_from_down, sinon à droite) et les remplace par l'opération "operation_fn"
+    fn push_binary(&mut self, vec_from_down:bool, instruction:Op, operation_fn:fn(Rc<EsianolopNode>,Rc<EsianolopNode>) -> EsianolopInstruction) -> Result<(),EsianolopError> {
+        if self.values.len() < 2 {
+            return Err(underflow(format!("'{}' to {}",instruction,if vec_from_down {"left"} else {"right"})))
+        }
 
-    // Execute du code Esianolop multilignes, retourne soit Ok(()), ou Err(message d'erreur)
-    pub fn parse_text(&mut self,text:&str) -> Result<(),String> {
-
-        //println!("Executing '{}'",text);
-        
-        // Pour chaque ligne
-        for (line_nb,line) in text.to_ascii_lowercase().split("\n").map(|x| x.to_owned()).enumerate() {
+        // Retirer les 2 premières valeures du stack / deux dernières (dans l'ordre du stack),
+        // instancier avec les deux valeures et mettre dans le stack
+        if vec_from_down {
+            let (a, b) = (self.values.pop_front().unwrap(), self.values.pop_front().unwrap());
+            self.values.push_front(EsianolopNode::new(operation_fn(Rc::new(a), Rc::new(b)), self.overflow));
+        } else {
+            let (b, a) = (self.values.pop_back().unwrap(), self.values.pop_back().unwrap());
+            self.values.push_back(EsianolopNode::new(operation_fn(Rc::new(a), Rc::new(b)), self.overflow));
+        };
+
+        Ok(()) // Tout est bon, on retourne Ok(()) !
+    }
 
-            // Retire les commentaires du code
-            let no_comment = line.split(";").collect::<Vec<&str>>()[0].split("#").collect::<Vec<&str>>()[0];
+    // Retire 1 valeur du stack et la remplace par l'opération "operation_fn"
+    fn push_unary(&mut self, vec_from_down:bool, instruction:Op, operation_fn:fn(Rc<EsianolopNode>) -> EsianolopInstruction) -> Result<(),EsianolopError> {
+        match vec_from_down { // Si on prend la valeure depuis la guache ou droite 
+            false => {
+                let temp = self.values.pop_back();
+                let val = match temp {
+                    Some(e) => e,
+                    None => return Err(underflow(format!("'{}' to right",instruction)))
+                };
+                self.values.push_back(EsianolopNode::new(operation_fn(Rc::new(val)), self.overflow));
+            },
+            true => {
+                let temp = self.values.pop_front();
+                let val = match temp {
+                    Some(e) => e,
+                    None => return Err(underflow(format!("'{}' to left",instruction)))
+                };
+                self.values.push_front(EsianolopNode::new(operation_fn(Rc::new(val)), self.overflow));
+            }
+        };
+        Ok(())
+    }
 
-            // On énumère toutes les instructions, mais avec la possibilité d'en skipper (en utillisant table.next()), pour les fonctions 
-            let mut table_iter = no_comment.split(" ").filter(|x| x.to_owned().trim() != "" ).enumerate();
-            
-            let mut active_instruction = table_iter.next();
-            let mut in_function = false;
-            let mut temp_lifetime_instruction:String;
-            while let Some((ins_nb, mut instruction)) = active_instruction {
+    // Ajoute un nombre au stack : devant si front, sinon derrière (par défault)
+    // En modulo N, le nombre est d'abord ramené entre 0 et N-1, comme les résultats des calculs
+    fn push_number(&mut self, front:bool, value:EsianolopValue) {
+        let value = self.overflow.reduce(value);
+        if front {
+            self.values.push_front(EsianolopNode::new(EsianolopInstruction::Num(value), self.overflow));
+        } else {
+            self.values.push_back(EsianolopNode::new(EsianolopInstruction::Num(value), self.overflow));
+        }
+    }
 
-                //println!("{}",instruction);
+    
 
-                if in_function {
-                    instruction = instruction.split(":").collect::<Vec<&str>>()[1];
-                    in_function = false;
-                }
 
-                if instruction.trim() == "" {active_instruction = table_iter.next();continue};
+    // Retire la valeur au bout choisi du stack (gauche si vec_from_down) et la calcule. Sert aux for, if et while.
+    fn take_value(&mut self, vec_from_down:bool, what:&str) -> Result<EsianolopValue,EsianolopError> {
+        let tree = if vec_from_down {
+            self.values.pop_front()
+        } else {
+            self.values.pop_back()
+        };
+        match tree {
+            Some(e) => e.execute().map_err(|e| e.with_note(format!("in accesing value of {}",what))),
+            None => Err(underflow(format!("'{}'",what)))
+        }
+    }
 
-                // Si c'est une définition de fonction/for, on skip j'usqu'a la fin de la def
-                if instruction.contains(":") {
+    // Retire n valeurs au bout choisi du stack et les calcule, dans l'ordre du stack. Sert aux range.
+    fn take_values(&mut self, vec_from_down:bool, n:usize, what:&str) -> Result<Vec<EsianolopValue>,EsianolopError> {
+        if self.values.len() < n {
+            return Err(underflow(format!("'{}' ({} values needed)",what,n)))
+        }
+        let trees = if vec_from_down {
+            self.values.drain(0..n).collect::<Vec<EsianolopNode>>()
+        } else {
+            self.values.drain((self.values.len()-n)..).collect::<Vec<EsianolopNode>>()
+        };
+        return trees.iter().map(|e| e.execute().map_err(|e| e.with_note(format!("in accesing values of {}",what)))).collect()
+    }
 
-                    //println!("Founded function at {}:{}",line_nb,ins_nb);
+    // Execute du code Esianolop multilignes, retourne soit Ok(()), ou Err(erreur)
+    // Le code est d'abord transformé en arbre (voir ast.rs), compilé en bytecode (voir bytecode.rs), puis executé
+    pub fn parse_text(&mut self,text:&str) -> Result<(),EsianolopError> {
+        // Une directive ne vaut que pour ce texte : on remet la politique d'avant à la fin.
+        // Les noeuds créés pendant l'exécution gardent la leur, les résultats ne changent donc pas.
+        let program = ast::parse(text)?;
+        let overflow = self.overflow;
+        let result = self.run(Rc::new(bytecode::compile(&program)));
+        self.overflow = overflow;
+        return result
+    }
 
-                    let mut line_rest = table_iter.clone().map(|(_,y)|y) // on prend le reste de la ligne (derrère l'instruction)
-                        .collect::<Vec<&str>>().join(" ").to_owned();
-                    
-                    line_rest = instruction.to_owned()+ " "+&line_rest; // On rajoute notre instruction à la ligne
-                    //println!("Line_rest:{}",line_rest);
-                    
-                    let function_name = line_rest.split(":").collect::<Vec<&str>>()[0].trim(); // On prend le nom de la fonction définie
-                    let mut function_code = line_rest.split(":").collect::<Vec<&str>>()[1]; // On prend le reste (le code dans la fonction ET le reste de la ligne)
-                    
+    // Affiche le bytecode d'une fonction, None si elle n'existe pas
+    pub fn disassemble(&self, name:&str) -> Option<String> {
+        return self.functions.get(name).map(|function| bytecode::disassemble(&function.bytecode))
+    }
 
-                    if function_code.contains(":") { // En cas de continuation du code derrière la fonction
-                        function_code = function_code.split(":").collect::<Vec<&str>>()[0]; // On le retire du code de la fonction
+    // La machine virtuelle : execute du bytecode sur le stack
+    fn run(&mut self, bytecode:Rc<Vec<Opcode>>) -> Result<(),EsianolopError> {
+        let mut frames = vec![Frame { bytecode, pc:0, call:None, loops:vec![] }];
+        let indices = self.loop_indices.len();
+
+        if let Err(mut error) = self.run_frames(&mut frames) {
+            // On remet l'interpréteur dans son état d'avant les boucles en cours
+            self.loop_indices.truncate(indices);
+            self.control = None;
+            // Ajout à l'erreur des informations de la trace : les boucles et appels en cours, du plus profond au moins profond
+            for frame in frames.iter().rev() {
+                for record in frame.loops.iter().rev() {
+                    let name = match record.kind {
+                        LoopKind::For {..} => "for",
+                        LoopKind::Range {..} => "range",
+                        LoopKind::While {..} => "while",
                     };
+                    error = error.traced(TraceEntry::Loop { name, span:record.span, iteration:record.iteration });
+                }
+                if let Some(call) = &frame.call {
+                    error = error.traced(TraceEntry::Call { name:call.name.to_owned(), span:call.span, defined:call.defined });
+                }
+            }
+            return Err(error)
+        }
+        Ok(())
+    }
 
-                    // Si le nom / code de la fonction est vide
-                    if (function_name == "") | (function_code.trim() == "") { return Err(format!("trying to define an empty function at {}:{}",line_nb,ins_nb))} 
-                    // Si on redéfinie la fonction
-                    if self.functions.contains_key(function_name) {return Err(format!("trying to define already-defined function at {}:{}",line_nb,ins_nb))}
-                    println!("Defing function {} with {}",function_name,function_code.trim());
-
-                    // On saute à la fin de la définition de la fonction, pour la prochaine instruction
-                    let skip_count = function_code.matches(' ').count();
-                    if skip_count == 0{
-                        // Si il y a pas d'espaces, on reste sur l'instrcution actuelle, mais retire la définition de la fonction
-                        in_function = false;
-                        temp_lifetime_instruction = instruction.split(":").map(|x|x.to_owned()).collect::<Vec<String>>().get(2..).unwrap().join(":");
-                        active_instruction = Some((ins_nb, &temp_lifetime_instruction));
-                    } else {
-                        // Sinon on saute et coupe le fin de définition
-                        active_instruction =table_iter.nth(skip_count-1);
-                        let (pos,ins) = match active_instruction {
-                            None  => {return Err(format!("Jump to function end failed at {}:{}",line_nb,ins_nb))},
-                            Some((pos,ins)) => (pos,ins),
+    fn run_frames(&mut self, frames:&mut Vec<Frame>) -> Result<(),EsianolopError> {
+        loop {
+            let frame = match frames.last_mut() {
+                Some(e) => e,
+                None => return Ok(()) // Tout c'est bien passé
+            };
+            let bytecode = Rc::clone(&frame.bytecode);
+            let opcode = match bytecode.get(frame.pc) {
+                Some(e) => e,
+                None => { // Fin de la fonction, on revient à l'appellant
+                    frames.pop();
+                    continue;
+                }
+            };
+            frame.pc += 1;
+
+            match opcode {
+                Opcode::Push { text, front, span } => match self.mode.parse_number(text) {
+                    Some(value) => self.push_number(*front, value),
+                    None => return Err(EsianolopError::new(EsianolopErrorKind::InvalidNumber { text: text.to_owned(), mode: self.mode }).at(*span)),
+                },
+                Opcode::Op { op, side, span } => {
+                    // On execute l'instruction, et si il y a une erreur, on l'affiche
+                    // "specified" est utile pour les nombres, car par défault on les ajoute à droite du stack
+                    self.execute_instruction(*side != Some(Side::Right), side.is_some(), *op).map_err(|e| e.at(*span))?;
+                    // Un "break" ou "continue" : on remonte les appels jusqu'à la boucle la plus proche
+                    if let Some(control) = self.control.take() {
+                        while frames.last().map(|frame| frame.loops.is_empty()).unwrap_or(false) {
+                            frames.pop();
+                        }
+                        let frame = match frames.last_mut() {
+                            Some(e) => e,
+                            None => return Ok(())
                         };
-                        temp_lifetime_instruction = ins.split(":").map(|x|x.to_owned()).collect::<Vec<String>>().get(1..).unwrap().join(":");
-                        active_instruction = Some((pos,&temp_lifetime_instruction));
+                        let end = frame.loops.last().map(|record| record.end).unwrap_or(frame.pc);
+                        if control == LoopControl::Break {
+                            self.exit_loop(frame);
+                        } else {
+                            frame.pc = end - 1; // Le Next de la boucle
+                        }
                     }
+                },
+                Opcode::Call { name, span } => {
+                    // On prend le code défini par la fonction (l'Rc évite de copier tout le bytecode)
+                    let function = match self.functions.get(name) {
+                        Some(e) => e,
+                        None => return Err(EsianolopError::new(EsianolopErrorKind::UnknownWord(name.to_owned())).at(*span))
+                    };
+                    println!("Executing function {} with {}",name,function.code);
+                    // Execute le code de la fonction (marche pour les fonctions récursive donc)
+                    frames.push(Frame { bytecode:Rc::clone(&function.bytecode), pc:0, call:Some(CallRecord { name:name.to_owned(), span:*span, defined:function.span }), loops:vec![] });
+                },
+                // On créé la fonction
+                Opcode::Define { name, function, span } => {
+                    // Si on redéfinie la fonction avec un autre code (avec le meme code, c'est possible : une définition dans une boucle est relue à chaque tour)
+                    match self.functions.get(name) {
+                        Some(e) if e.code == function.code => (),
+                        Some(_) => return Err(EsianolopError::new(EsianolopErrorKind::Redefinition(name.to_owned())).at(*span)),
+                        None => {
+                            println!("Defing function {} with {}",name,function.code);
+                            self.functions.insert(name.to_owned(), function.clone()); // Ajouter la fonction à la hashmap de fonctions
+                        }
+                    }
+                },
+                // Contrairement aux instructions, les for/if/range/while prennent leur valeur à droite par défault
+                // Le if a deux codes à la suite : "if:alors:sinon:"
+                Opcode::If { side, otherwise, span } => {
+                    let condition = match self.take_value(*side == Some(Side::Left),"if") {
+                        Ok(e) => e,
+                        Err(e) => return Err(e.at(*span))
+                    };
+                    if !condition.is_true() {
+                        frame.pc = *otherwise;
+                    }
+                },
+                Opcode::Jump(target) => frame.pc = *target,
+                // La directive "#!overflow" : les opérations suivantes utilisent cette politique
+                Opcode::Overflow(policy) => self.overflow = *policy,
+                Opcode::For { side, end, span } => {
+
+                    let nb = match self.take_value(*side == Some(Side::Left),"for") {
+                        Ok(e) => e,
+                        Err(e) => return Err(e.at(*span))
+                    }; // On récupère le nombre de boucle (et on le retire du stack)
+                    if !nb.is_integer() || nb.is_negative() {
+                        return Err(EsianolopError::new(EsianolopErrorKind::InvalidLoop(format!("loop count must be a non-negative integer, got {}",nb))).at(*span))
+                    }
+                    let nb = match nb.to_i64() {
+                        Some(e) => e,
+                        None => return Err(EsianolopError::new(EsianolopErrorKind::InvalidLoop(format!("too many iterations ({}) in for loop",nb))).at(*span))
+                    };
 
-                    match function_name {
-                        // Si on définie une fonction avec le nom "for", on execute le code spécial for
-                        "for" | "<for" | ">for" => {
-
-                            // si il n'y a pas de valeurs pour executer la boucle
-                            if self.values.len() == 0 {
-                                return Err(format!("Aptended a for with nothing in the stack at {}:{}",line_nb,ins_nb))
-                            }
-
-                            // On prend le premier charactère du for, pour savoir si c'est < ou pas
-                            let index = if &function_name[0..1] == "<" {0} else {self.values.len()-1};
-                            let nb = match self.values[index].execute() {
-                                Ok(e) => e,
-                                Err(e) => return Err(format!("{} in accesing number of for loop at {}:{}",e,line_nb,ins_nb))
-                            }; // On récupère le nombre de boucle 
+                    if nb == 0 {
+                        frame.pc = *end;
+                    } else {
+                        self.loop_indices.push(self.mode.from_i64(0));
+                        frame.loops.push(LoopRecord { kind:LoopKind::For { index:0, count:nb }, body:frame.pc, end:*end, span:*span, iteration:1 });
+                    }
+                },
+                // Le range prend 3 valeurs : "début fin pas range:code:". "i" va de début (inclus) à fin (exclus).
+                Opcode::Range { side, end, span } => {
 
-                            self.values.remove(index); // On retire le nombre d'execution
+                    let (start, range_end, step) = match self.take_values(*side == Some(Side::Left),3,"range") {
+                        Ok(e) => (e[0].clone(),e[1].clone(),e[2].clone()),
+                        Err(e) => return Err(e.at(*span))
+                    };
+                    let direction = step.compare(&EsianolopValue::Int(0));
+                    if direction == Ordering::Equal {
+                        return Err(EsianolopError::new(EsianolopErrorKind::InvalidLoop("zero step in range".to_owned())).at(*span))
+                    }
 
-                            for _ in 0..nb { // On execute le for
-                                match self.parse_text(function_code.trim()) {
-                                    Err(e) => return Err(format!("{} in for loop at {}:{}",e,line_nb+1,ins_nb+1)),
-                                    Ok(_)=> (),
-                                }
+                    // On avance tant qu'on n'a pas dépassé la fin, dans le sens du pas
+                    if start.compare(&range_end) != direction.reverse() {
+                        frame.pc = *end;
+                    } else {
+                        self.loop_indices.push(start.clone());
+                        let kind = LoopKind::Range { index:start, end:range_end, step, direction };
+                        frame.loops.push(LoopRecord { kind, body:frame.pc, end:*end, span:*span, iteration:1 });
+                    }
+                },
+                // Le while a aussi deux codes : "while:condition:corps:"
+                // La condition fait partie de la boucle : "i" et "break" y marchent aussi
+                Opcode::While { end, span } => {
+                    self.loop_indices.push(self.mode.from_i64(0));
+                    frame.loops.push(LoopRecord { kind:LoopKind::While { index:0 }, body:frame.pc, end:*end, span:*span, iteration:1 });
+                },
+                // Après la condition, on retire une valeur du stack : on continue tant qu'elle est non nulle
+                Opcode::WhileTest { side } => {
+                    let condition = match self.take_value(*side == Some(Side::Left),"while") {
+                        Ok(e) => e,
+                        Err(e) => return Err(match frame.loops.last() {Some(record) => e.at(record.span), None => e})
+                    };
+                    if !condition.is_true() {
+                        self.exit_loop(frame);
+                    }
+                },
+                // Fin du corps : on passe au tour suivant, ou on sort de la boucle
+                Opcode::Next => {
+                    let record = match frame.loops.last_mut() {
+                        Some(e) => e,
+                        None => continue
+                    };
+                    let index = match &mut record.kind {
+                        LoopKind::For { index, count } => {
+                            *index += 1;
+                            if index < count {Some(self.mode.from_i64(*index))} else {None}
+                        },
+                        LoopKind::Range { index, end, step, direction } => {
+                            match index.checked_add(step) {
+                                Ok(next) if next.compare(end) == direction.reverse() => {
+                                    *index = next;
+                                    Some(index.clone())
+                                },
+                                // La prochaine valeur a dépassé la fin (ou déborde : elle l'aurait forcément dépassé)
+                                _ => None
                             }
-                        }
-                        // Sinon, on créé la fonction
-                        _ => {
-                            self.functions.insert(function_name.to_owned(), function_code.trim().to_owned()); // Ajouter la fonction à la hashmap de fonctions
-                            //println!("Defined !");
+                        },
+                        LoopKind::While { index } => {
+                            *index += 1;
+                            Some(self.mode.from_i64(*index))
                         }
                     };
-                    continue;
-                }
-
-                // On charge l'instruction suivante pour la prochaine boucle
-                active_instruction =table_iter.next();
-
-                // On test si il y a un "<" ou ">" devant
-                let mut vec_from_down = true;
-                let mut specified = false;
-                if (instruction.len() >= 2) & (instruction.chars().nth(0) == Some('<') || instruction.chars().nth(0) == Some('>')) {
-                    vec_from_down = instruction.chars().nth(0) == Some('<');
-                    instruction = instruction.chars().next().map(|c| &instruction[c.len_utf8()..]).unwrap();
-                    specified = true; // Utile pour les nombres, car par défault on l'ajoute à droite du stack
+                    match index {
+                        Some(index) => {
+                            frame.pc = record.body;
+                            record.iteration += 1;
+                            if let Some(last) = self.loop_indices.last_mut() {
+                                *last = index;
+                            }
+                        },
+                        None => self.exit_loop(frame)
+                    }
                 }
-
-                // On execute le code, et si il y a une erreur, on l'affiche
-                match self.execute_instruction(vec_from_down, specified, &instruction.to_ascii_lowercase()) {
-                    
-                    Err(e) => {
-                        
-                        return Err(format!("Error at {}:{}, {{\n\t{}\n}}",line_nb+1,ins_nb+1,e.replace("\n", "\n\t"))) 
-                    },
-                    _ => (),
-                };
             }
-            
         }
-        Ok(()) // Tout c'est bien passé, on retourne Ok(())
     }
 
-}
\ No newline at end of file
+    // Sort de la boucle la plus profonde d'un appel
+    fn exit_loop(&mut self, frame:&mut Frame) {
+        if let Some(record) = frame.loops.pop() {
+            self.loop_indices.pop();
+            frame.pc = record.end;
+        }
+    }
+}
@@ -0,0 +1,942 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::bigint::BigInt;
+use crate::error::EsianolopErrorKind;
+use crate::rational::Rational;
+
+// Au delà, "fact" et "choose" feraient des milliers de multiplications de très grands nombres
+const MAX_FACTORIAL: i64 = 10_000;
+
+// La taille maximale (en bits) du résultat d'une puissance ou d'un décalage d'entiers illimités
+const MAX_BITS: u64 = 100_000;
+
+// La taille maximale (en bits) d'un nombre pour "isprime" et "nextprime" : au delà, les tests de Miller-Rabin deviennent trop lents
+const MAX_PRIME_BITS: u64 = 512;
+
+// Le nombre de bits de a^exp : floor(exp * log2|a|) + 1, avec log2|a| calculé sur les 64 premiers bits de a.
+// 0, 1 et -1 restent petits quel que soit l'exposant.
+fn pow_bits(a: &BigInt, exp: u64) -> f64 {
+    if a.bits() <= 1 {
+        return a.bits() as f64;
+    }
+    let shift = a.bits().saturating_sub(64);
+    let log2 = (a.abs().shr(shift).low_u64() as f64).log2() + shift as f64;
+    return (exp as f64 * log2).floor() + 1.0;
+}
+
+// Vérifie qu'un résultat de "bits" bits ne dépasse pas MAX_BITS (pour la puissance et le décalage)
+fn check_bits(bits: f64, error: &'static str) -> Result<(), EsianolopErrorKind> {
+    if bits > MAX_BITS as f64 {
+        return Err(EsianolopErrorKind::InvalidOperand(error));
+    }
+    Ok(())
+}
+
+// Le mode de calcul d'un interpréteur : il décide du type des nombres écrits dans le code
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EsianolopMode {
+    Int, // Entiers signés sur 64 bits, erreur en cas de dépassement
+    Big, // Entiers de taille arbitraire, jamais de dépassement
+    Rational, // Fractions exactes, la division ne tronque plus
+    Float, // Flottants 64 bits, pour les racines et fonctions comme log, sin...
+}
+
+impl EsianolopMode {
+
+    // Retrouve un mode à partir de son nom (pour la ligne de commande)
+    pub fn from_name(name: &str) -> Option<EsianolopMode> {
+        match name {
+            "int" | "i64" => Some(EsianolopMode::Int),
+            "big" | "bigint" => Some(EsianolopMode::Big),
+            "rational" | "rat" | "frac" => Some(EsianolopMode::Rational),
+            "float" | "f64" => Some(EsianolopMode::Float),
+            _ => None,
+        }
+    }
+
+    // Lit un nombre écrit dans le code, dans le type du mode
+    pub fn parse_number(&self, text: &str) -> Option<EsianolopValue> {
+        // Les "_" ne servent qu'à la lisibilité (1_000_000) : seulement entre deux chiffres de la base du nombre
+        // (pas 0x_1f, 1_e6 ni 1e_6)
+        let radix = match text.trim_start_matches(['-', '+']).get(..2) {
+            Some("0x") => 16,
+            Some("0b") => 2,
+            Some("0o") => 8,
+            _ => 10,
+        };
+        let chars = text.chars().collect::<Vec<char>>();
+        for (i, c) in chars.iter().enumerate() {
+            if *c == '_' && !(i > 0 && chars[i - 1].is_digit(radix) && chars.get(i + 1).map(|x| x.is_digit(radix)).unwrap_or(false)) {
+                return None;
+            }
+        }
+        let text = &text.replace('_', "");
+        // Les entiers en hexadécimal (0x1f), binaire (0b1010) ou octal (0o17) s'écrivent dans tous les modes
+        if let Some(value) = parse_radix(text) {
+            return match self {
+                EsianolopMode::Int => value.to_i64().map(EsianolopValue::Int),
+                EsianolopMode::Big => Some(EsianolopValue::Big(value)),
+                EsianolopMode::Rational => Some(EsianolopValue::Rational(Rational::from(value))),
+                EsianolopMode::Float => Some(value.to_f64()).filter(|x| x.is_finite()).map(EsianolopValue::Float),
+            };
+        }
+        match self {
+            // En notation scientifique (1e6), un entier n'est accepté que si la valeur est exacte (pas 1.5e-3)
+            EsianolopMode::Int => text.parse::<i64>().ok().or_else(|| parse_scientific(text)?.to_i64()).map(EsianolopValue::Int),
+            EsianolopMode::Big => text.parse::<BigInt>().ok().or_else(|| parse_scientific(text)).map(EsianolopValue::Big),
+            EsianolopMode::Rational => text.parse::<Rational>().ok().map(EsianolopValue::Rational),
+            // On refuse "inf" et "nan", qui sont lus par rust comme des flottants
+            EsianolopMode::Float => text.parse::<f64>().ok().filter(|x| x.is_finite()).map(EsianolopValue::Float),
+        }
+    }
+}
+
+// Lit un entier en hexadécimal (0x1f), binaire (0b1010) ou octal (0o17), avec éventuellement un signe devant
+fn parse_radix(text: &str) -> Option<BigInt> {
+    let (negative, rest) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let radix = match rest.get(..2)? {
+        "0x" => 16,
+        "0b" => 2,
+        "0o" => 8,
+        _ => return None,
+    };
+    if rest.len() == 2 {
+        return None;
+    }
+    let mut res = BigInt::zero();
+    for c in rest[2..].chars() {
+        res = &(&res * &BigInt::from(radix as i64)) + &BigInt::from(c.to_digit(radix)? as i64);
+    }
+    return Some(if negative { -&res } else { res });
+}
+
+// Lit un entier écrit en notation scientifique ("1e6", "2.5e3"), None s'il n'est pas entier
+fn parse_scientific(text: &str) -> Option<BigInt> {
+    if !text.contains('e') {
+        return None;
+    }
+    let value = text.parse::<Rational>().ok()?;
+    if value.is_integer() { Some(value.numerator().clone()) } else { None }
+}
+
+impl EsianolopMode {
+
+    // Convertis un entier dans le type du mode (pour les numéros de tours de boucles)
+    pub fn from_i64(&self, val: i64) -> EsianolopValue {
+        match self {
+            EsianolopMode::Int => EsianolopValue::Int(val),
+            EsianolopMode::Big => EsianolopValue::Big(BigInt::from(val)),
+            EsianolopMode::Rational => EsianolopValue::Rational(Rational::from(BigInt::from(val))),
+            EsianolopMode::Float => EsianolopValue::Float(val as f64),
+        }
+    }
+}
+
+impl fmt::Display for EsianolopMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EsianolopMode::Int => write!(f, "int"),
+            EsianolopMode::Big => write!(f, "big"),
+            EsianolopMode::Rational => write!(f, "rational"),
+            EsianolopMode::Float => write!(f, "float"),
+        }
+    }
+}
+
+// Ce qu'on fait quand un calcul d'entiers 64 bits (mode int) déborde, ou pour calculer modulo N
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    Checked,       // Erreur en cas de dépassement (par défault)
+    Wrapping,      // On garde les 64 bits du bas, comme en complément à deux (i64::MAX + 1 donne i64::MIN)
+    Saturating,    // On s'arrete à i64::MIN / i64::MAX
+    Modular(i64),  // Tout entier (nombres écrits et résultats, aussi pour les BigInt) est ramené entre 0 et N-1
+}
+
+impl OverflowPolicy {
+
+    // Retrouve une politique à partir de son nom : "checked", "wrapping", "saturating" ou "mod N" (aussi "mod:N")
+    pub fn from_name(name: &str) -> Option<OverflowPolicy> {
+        let mut words = name.split(|c: char| c.is_whitespace() || c == ':' || c == '=').filter(|x| !x.is_empty());
+        let policy = match words.next()? {
+            "checked" => OverflowPolicy::Checked,
+            "wrapping" | "wrap" => OverflowPolicy::Wrapping,
+            "saturating" | "sat" => OverflowPolicy::Saturating,
+            "mod" | "modular" => {
+                let n = words.next()?.parse::<i64>().ok().filter(|n| *n > 0)?;
+                OverflowPolicy::Modular(n)
+            },
+            _ => return None,
+        };
+        if words.next().is_some() {
+            return None;
+        }
+        return Some(policy);
+    }
+
+    // Ramène un résultat entier entre 0 et N-1 pour le modulo. Ne change rien pour les autres politiques,
+    // ni pour les fractions et les flottants.
+    pub fn reduce(&self, value: EsianolopValue) -> EsianolopValue {
+        let n = match self {
+            OverflowPolicy::Modular(n) => *n,
+            _ => return value,
+        };
+        match value {
+            EsianolopValue::Int(a) => EsianolopValue::Int(a.rem_euclid(n)),
+            EsianolopValue::Big(a) => {
+                let n = BigInt::from(n);
+                let (_, r) = a.div_rem(&n).unwrap();
+                EsianolopValue::Big(if r.is_negative() { &r + &n } else { r })
+            },
+            _ => value,
+        }
+    }
+
+    // Ramène un résultat entier exact dans un i64 (pour le mode int), comme fit_i64
+    fn fit_big(&self, exact: BigInt, what: &'static str) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let res = match self {
+            OverflowPolicy::Checked => exact.to_i64().ok_or(EsianolopErrorKind::Overflow(what))?,
+            OverflowPolicy::Wrapping => exact.low_u64() as i64,
+            OverflowPolicy::Saturating => exact.to_i64().unwrap_or(if exact.is_negative() { i64::MIN } else { i64::MAX }),
+            OverflowPolicy::Modular(_) => return Ok(self.reduce(EsianolopValue::Big(exact)).to_i64().map(EsianolopValue::Int).unwrap()),
+        };
+        return Ok(EsianolopValue::Int(res));
+    }
+
+    // Ramène le résultat exact d'une opération entre deux i64 (calculé en i128, où il ne peut pas déborder) dans un i64
+    fn fit_i64(&self, exact: i128, what: &'static str) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let res = match self {
+            OverflowPolicy::Checked => i64::try_from(exact).map_err(|_| EsianolopErrorKind::Overflow(what))?,
+            OverflowPolicy::Wrapping => exact as i64, // La conversion garde les 64 bits du bas
+            OverflowPolicy::Saturating => exact.clamp(i64::MIN as i128, i64::MAX as i128) as i64,
+            OverflowPolicy::Modular(n) => exact.rem_euclid(*n as i128) as i64,
+        };
+        return Ok(EsianolopValue::Int(res));
+    }
+}
+
+impl fmt::Display for OverflowPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OverflowPolicy::Checked => write!(f, "checked"),
+            OverflowPolicy::Wrapping => write!(f, "wrapping"),
+            OverflowPolicy::Saturating => write!(f, "saturating"),
+            OverflowPolicy::Modular(n) => write!(f, "mod {}", n),
+        }
+    }
+}
+
+
+// Une valeur calculée par un arbre.
+// Quand on mélange deux types, le résultat prend le plus "large" des deux (Int < Big < Rational < Float).
+#[derive(Clone, Debug, PartialEq)]
+pub enum EsianolopValue {
+    Int(i64),
+    Big(BigInt),
+    Rational(Rational),
+    Float(f64), // Jamais infini ni NaN : ces résultats sont des erreurs
+}
+
+// Vérifie qu'un résultat flottant est bien un nombre fini
+fn finite(val: f64, what: &'static str) -> Result<EsianolopValue, EsianolopErrorKind> {
+    if val.is_finite() { Ok(EsianolopValue::Float(val)) } else { Err(EsianolopErrorKind::NotFinite(what)) }
+}
+
+impl EsianolopValue {
+
+    // Convertis un entier en BigInt (None pour une fraction ou un flottant)
+    fn to_big(&self) -> Option<BigInt> {
+        match self {
+            EsianolopValue::Int(a) => Some(BigInt::from(*a)),
+            EsianolopValue::Big(a) => Some(a.clone()),
+            _ => None,
+        }
+    }
+
+    // Convertis la valeur en fraction (toujours possible, sauf pour les flottants)
+    fn to_rational(&self) -> Rational {
+        match self {
+            EsianolopValue::Rational(a) => a.clone(),
+            _ => Rational::from(self.to_big().unwrap()),
+        }
+    }
+
+    // Valeur approchée en flottant
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            EsianolopValue::Int(a) => *a as f64,
+            EsianolopValue::Big(a) => a.to_f64(),
+            EsianolopValue::Rational(a) => a.to_f64(),
+            EsianolopValue::Float(a) => *a,
+        }
+    }
+
+    fn is_float(&self) -> bool {
+        matches!(self, EsianolopValue::Float(_))
+    }
+
+    // Vrai si la valeur est entière (3, 6/2, 3.0), meme si elle ne tient pas dans un i64
+    pub fn is_integer(&self) -> bool {
+        match self {
+            EsianolopValue::Int(_) | EsianolopValue::Big(_) => true,
+            EsianolopValue::Rational(a) => a.is_integer(),
+            EsianolopValue::Float(a) => a.fract() == 0.0,
+        }
+    }
+
+    // Retourne la valeur en i64 si c'est un entier qui y tient (utile pour les nombres de boucles)
+    pub fn to_i64(&self) -> Option<i64> {
+        match self {
+            EsianolopValue::Int(a) => Some(*a),
+            EsianolopValue::Big(a) => a.to_i64(),
+            EsianolopValue::Rational(a) => if a.is_integer() {a.numerator().to_i64()} else {None},
+            EsianolopValue::Float(a) => if a.fract() == 0.0 && a.abs() < 9.0e18 {Some(*a as i64)} else {None},
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        match self {
+            EsianolopValue::Int(a) => *a < 0,
+            EsianolopValue::Big(a) => a.is_negative(),
+            EsianolopValue::Rational(a) => a.is_negative(),
+            EsianolopValue::Float(a) => *a < 0.0,
+        }
+    }
+
+    // Une valeur est "vraie" si elle n'est pas nulle
+    pub fn is_true(&self) -> bool {
+        match self {
+            EsianolopValue::Int(a) => *a != 0,
+            EsianolopValue::Big(a) => !a.is_zero(),
+            EsianolopValue::Rational(a) => !a.is_zero(),
+            EsianolopValue::Float(a) => *a != 0.0,
+        }
+    }
+
+    // Les booléens sont des entiers : 1 pour vrai et 0 pour faux
+    pub fn from_bool(val: bool) -> EsianolopValue {
+        EsianolopValue::Int(val as i64)
+    }
+
+    // Compare deux valeurs, en passant par le type le plus large des deux
+    pub fn compare(&self, other: &EsianolopValue) -> Ordering {
+        match (self, other) {
+            (EsianolopValue::Int(a), EsianolopValue::Int(b)) => a.cmp(b),
+            // Pas de NaN possible, la comparaison de flottants marche toujours
+            _ if self.is_float() || other.is_float() => self.to_f64().partial_cmp(&other.to_f64()).unwrap_or(Ordering::Equal),
+            (EsianolopValue::Rational(_), _) | (_, EsianolopValue::Rational(_)) => self.to_rational().cmp(&other.to_rational()),
+            _ => self.to_big().unwrap().cmp(&other.to_big().unwrap()),
+        }
+    }
+
+    // Affichage de la valeur: les fractions peuvent etre affichées en décimal avec "decimals" chiffres après la virgule
+    pub fn to_string_with(&self, decimals: Option<usize>) -> String {
+        match (self, decimals) {
+            (EsianolopValue::Rational(a), Some(digits)) => a.to_decimal_string(digits),
+            _ => self.to_string(),
+        }
+    }
+
+    pub fn checked_add(&self, other: &EsianolopValue) -> Result<EsianolopValue, EsianolopErrorKind> {
+        match (self, other) {
+            (EsianolopValue::Int(a), EsianolopValue::Int(b)) => a.checked_add(*b).map(EsianolopValue::Int).ok_or(EsianolopErrorKind::Overflow("addition")),
+            _ if self.is_float() || other.is_float() => finite(self.to_f64() + other.to_f64(), "addition"),
+            (EsianolopValue::Rational(_), _) | (_, EsianolopValue::Rational(_)) => Ok(EsianolopValue::Rational(self.to_rational().add(&other.to_rational()))),
+            _ => Ok(EsianolopValue::Big(&self.to_big().unwrap() + &other.to_big().unwrap())),
+        }
+    }
+
+    pub fn checked_sub(&self, other: &EsianolopValue) -> Result<EsianolopValue, EsianolopErrorKind> {
+        match (self, other) {
+            (EsianolopValue::Int(a), EsianolopValue::Int(b)) => a.checked_sub(*b).map(EsianolopValue::Int).ok_or(EsianolopErrorKind::Overflow("substraction")),
+            _ if self.is_float() || other.is_float() => finite(self.to_f64() - other.to_f64(), "substraction"),
+            (EsianolopValue::Rational(_), _) | (_, EsianolopValue::Rational(_)) => Ok(EsianolopValue::Rational(self.to_rational().sub(&other.to_rational()))),
+            _ => Ok(EsianolopValue::Big(&self.to_big().unwrap() - &other.to_big().unwrap())),
+        }
+    }
+
+    pub fn checked_mul(&self, other: &EsianolopValue) -> Result<EsianolopValue, EsianolopErrorKind> {
+        match (self, other) {
+            (EsianolopValue::Int(a), EsianolopValue::Int(b)) => a.checked_mul(*b).map(EsianolopValue::Int).ok_or(EsianolopErrorKind::Overflow("multiplication")),
+            _ if self.is_float() || other.is_float() => finite(self.to_f64() * other.to_f64(), "multiplication"),
+            (EsianolopValue::Rational(_), _) | (_, EsianolopValue::Rational(_)) => Ok(EsianolopValue::Rational(self.to_rational().mul(&other.to_rational()))),
+            _ => Ok(EsianolopValue::Big(&self.to_big().unwrap() * &other.to_big().unwrap())),
+        }
+    }
+
+    pub fn checked_div(&self, other: &EsianolopValue) -> Result<EsianolopValue, EsianolopErrorKind> {
+        if !other.is_true() {
+            return Err(EsianolopErrorKind::DivisionByZero);
+        }
+        match (self, other) {
+            // Le seul débordement possible : i64::MIN / -1
+            (EsianolopValue::Int(a), EsianolopValue::Int(b)) => a.checked_div(*b).map(EsianolopValue::Int).ok_or(EsianolopErrorKind::Overflow("division")),
+            _ if self.is_float() || other.is_float() => finite(self.to_f64() / other.to_f64(), "division"),
+            // Avec une fraction, la division est exacte
+            (EsianolopValue::Rational(_), _) | (_, EsianolopValue::Rational(_)) => self.to_rational().div(&other.to_rational()).map(EsianolopValue::Rational).ok_or(EsianolopErrorKind::DivisionByZero),
+            _ => self.to_big().unwrap().div_rem(&other.to_big().unwrap()).map(|(q, _)| EsianolopValue::Big(q)).ok_or(EsianolopErrorKind::DivisionByZero),
+        }
+    }
+
+    // Reste de la division tronquée : il a le signe de self, comme le % de rust ("-7 2 rem" donne -1)
+    pub fn checked_rem(&self, other: &EsianolopValue) -> Result<EsianolopValue, EsianolopErrorKind> {
+        if !other.is_true() {
+            return Err(EsianolopErrorKind::DivisionByZero);
+        }
+        match (self, other) {
+            // i64::MIN % -1 déborde en rust, mais le reste vaut bien 0
+            (EsianolopValue::Int(a), EsianolopValue::Int(b)) => Ok(EsianolopValue::Int(a.wrapping_rem(*b))),
+            _ if self.is_float() || other.is_float() => finite(self.to_f64() % other.to_f64(), "remainder"),
+            // Avec une fraction : a - b * (a/b tronqué)
+            (EsianolopValue::Rational(_), _) | (_, EsianolopValue::Rational(_)) => {
+                let (a, b) = (self.to_rational(), other.to_rational());
+                let q = a.div(&b).ok_or(EsianolopErrorKind::DivisionByZero)?;
+                let q = Rational::from(if q.is_negative() { q.ceil() } else { q.floor() });
+                Ok(EsianolopValue::Rational(a.sub(&b.mul(&q))))
+            },
+            _ => self.to_big().unwrap().div_rem(&other.to_big().unwrap()).map(|(_, r)| EsianolopValue::Big(r)).ok_or(EsianolopErrorKind::DivisionByZero),
+        }
+    }
+
+    // Modulo : le reste de la division arrondie en dessous, il a le signe de other ("-7 2 mod" donne 1)
+    pub fn checked_mod(&self, other: &EsianolopValue) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let r = self.checked_rem(other)?;
+        if r.is_true() && r.is_negative() != other.is_negative() {
+            return r.checked_add(other);
+        }
+        return Ok(r);
+    }
+
+    // Division arrondie en dessous ("-7 2 fdiv" donne -4, là où "div" donne -3)
+    pub fn checked_fdiv(&self, other: &EsianolopValue) -> Result<EsianolopValue, EsianolopErrorKind> {
+        if !other.is_true() {
+            return Err(EsianolopErrorKind::DivisionByZero);
+        }
+        match (self, other) {
+            _ if self.is_float() || other.is_float() => finite((self.to_f64() / other.to_f64()).floor(), "floor division"),
+            (EsianolopValue::Rational(_), _) | (_, EsianolopValue::Rational(_)) => {
+                let q = self.to_rational().div(&other.to_rational()).ok_or(EsianolopErrorKind::DivisionByZero)?;
+                Ok(EsianolopValue::Rational(Rational::from(q.floor())))
+            },
+            // Pour les entiers, on corrige la division tronquée quand le reste n'a pas le signe du diviseur
+            _ => {
+                let q = self.checked_div(other)?;
+                let r = self.checked_rem(other)?;
+                if r.is_true() && r.is_negative() != other.is_negative() {
+                    return q.checked_sub(&EsianolopValue::Int(1));
+                }
+                Ok(q)
+            },
+        }
+    }
+
+    pub fn checked_pow(&self, other: &EsianolopValue) -> Result<EsianolopValue, EsianolopErrorKind> {
+        // Les flottants acceptent tout exposant, tant que le résultat est un nombre
+        if self.is_float() || other.is_float() {
+            return finite(self.to_f64().powf(other.to_f64()), "powering");
+        }
+        // Une fraction peut etre mise à une puissance négative (on l'inverse), mais l'exposant doit rester entier
+        if let EsianolopValue::Rational(a) = self {
+            let exp = other.to_i64().ok_or(EsianolopErrorKind::InvalidOperand("non-integer or too big exponent in powering"))?;
+            let bits = f64::max(pow_bits(a.numerator(), exp.unsigned_abs()), pow_bits(a.denominator(), exp.unsigned_abs()));
+            check_bits(bits, "too big result in powering (more than 100000 bits)")?;
+            return a.pow(exp).map(EsianolopValue::Rational).ok_or(EsianolopErrorKind::DivisionByZero);
+        }
+        // Pour les entiers, l'exposant doit etre positif et tenir dans un u32
+        let exp = other.to_i64().and_then(|e| u32::try_from(e).ok()).ok_or(EsianolopErrorKind::InvalidOperand("negative or too big exponent in powering"))?;
+        match self {
+            EsianolopValue::Int(a) => a.checked_pow(exp).map(EsianolopValue::Int).ok_or(EsianolopErrorKind::Overflow("powering")),
+            _ => {
+                let a = self.to_big().unwrap();
+                check_bits(pow_bits(&a, exp as u64), "too big result in powering (more than 100000 bits)")?;
+                Ok(EsianolopValue::Big(a.pow(exp)))
+            },
+        }
+    }
+
+    // Les opérations selon la politique de débordement. Entre deux i64, le résultat est calculé exactement en i128
+    // puis ramené dans un i64 ; sinon on fait l'opération habituelle, et on applique le modulo s'il y en a un.
+    fn with_overflow(&self, other: &EsianolopValue, overflow: OverflowPolicy, what: &'static str,
+                     exact: fn(i128, i128) -> i128,
+                     checked: fn(&EsianolopValue, &EsianolopValue) -> Result<EsianolopValue, EsianolopErrorKind>) -> Result<EsianolopValue, EsianolopErrorKind> {
+        match (self, other) {
+            (EsianolopValue::Int(a), EsianolopValue::Int(b)) => overflow.fit_i64(exact(*a as i128, *b as i128), what),
+            _ => checked(self, other).map(|x| overflow.reduce(x)),
+        }
+    }
+
+    pub fn add_with(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        self.with_overflow(other, overflow, "addition", |a, b| a + b, EsianolopValue::checked_add)
+    }
+
+    pub fn sub_with(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        self.with_overflow(other, overflow, "substraction", |a, b| a - b, EsianolopValue::checked_sub)
+    }
+
+    pub fn mul_with(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        self.with_overflow(other, overflow, "multiplication", |a, b| a * b, EsianolopValue::checked_mul)
+    }
+
+    pub fn div_with(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        if !other.is_true() {
+            return Err(EsianolopErrorKind::DivisionByZero);
+        }
+        self.with_overflow(other, overflow, "division", |a, b| a / b, EsianolopValue::checked_div)
+    }
+
+    pub fn fdiv_with(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        if !other.is_true() {
+            return Err(EsianolopErrorKind::DivisionByZero);
+        }
+        let floor_div = |a: i128, b: i128| if a % b != 0 && (a % b < 0) != (b < 0) { a / b - 1 } else { a / b };
+        self.with_overflow(other, overflow, "floor division", floor_div, EsianolopValue::checked_fdiv)
+    }
+
+    // La puissance ne tient pas forcément dans un i128 : en modulo on l'a calcule par carrés successifs, modulo N à chaque étape
+    pub fn pow_with(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let (a, b) = match (self, other) {
+            (EsianolopValue::Int(a), EsianolopValue::Int(b)) => (*a, *b),
+            _ => return self.checked_pow(other).map(|x| overflow.reduce(x)),
+        };
+        let exp = u32::try_from(b).map_err(|_| EsianolopErrorKind::InvalidOperand("negative or too big exponent in powering"))?;
+        let res = match overflow {
+            OverflowPolicy::Checked => a.checked_pow(exp).ok_or(EsianolopErrorKind::Overflow("powering"))?,
+            OverflowPolicy::Wrapping => a.wrapping_pow(exp),
+            OverflowPolicy::Saturating => a.saturating_pow(exp),
+            OverflowPolicy::Modular(n) => {
+                let n = n as i128;
+                let (mut base, mut exp, mut res) = ((a as i128).rem_euclid(n), exp, 1 % n);
+                while exp > 0 {
+                    if exp & 1 == 1 {
+                        res = res * base % n;
+                    }
+                    base = base * base % n;
+                    exp >>= 1;
+                }
+                res as i64
+            },
+        };
+        return Ok(EsianolopValue::Int(res));
+    }
+
+    // ----- Arithmétique des entiers -----
+
+    // La valeur en BigInt si c'est un entier (meme écrit en fraction ou en flottant), sinon une erreur pour "what"
+    fn to_integer(&self, what: &'static str) -> Result<BigInt, EsianolopErrorKind> {
+        match self {
+            EsianolopValue::Rational(a) if a.is_integer() => Ok(a.numerator().clone()),
+            EsianolopValue::Float(a) if a.fract() == 0.0 => Ok(BigInt::from_f64(*a).unwrap()),
+            _ => self.to_big().ok_or(EsianolopErrorKind::NotInteger(what)),
+        }
+    }
+
+    // Remet un résultat entier dans le type le plus large de "values" (et applique la politique de débordement)
+    fn from_integer(values: &[&EsianolopValue], res: BigInt, overflow: OverflowPolicy, what: &'static str) -> Result<EsianolopValue, EsianolopErrorKind> {
+        if values.iter().any(|x| x.is_float()) {
+            return finite(res.to_f64(), what);
+        }
+        if values.iter().any(|x| matches!(x, EsianolopValue::Rational(_))) {
+            let res = overflow.reduce(EsianolopValue::Big(res)).to_big().unwrap();
+            return Ok(EsianolopValue::Rational(Rational::from(res)));
+        }
+        if values.iter().any(|x| matches!(x, EsianolopValue::Big(_))) {
+            return Ok(overflow.reduce(EsianolopValue::Big(res)));
+        }
+        return overflow.fit_big(res, what);
+    }
+
+    // Plus grand diviseur commun (toujours positif, gcd(0, 0) vaut 0)
+    pub fn gcd(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let res = self.to_integer("gcd")?.gcd(&other.to_integer("gcd")?);
+        return EsianolopValue::from_integer(&[self, other], res, overflow, "gcd");
+    }
+
+    // Plus petit multiple commun (toujours positif, 0 si l'un des deux est nul)
+    pub fn lcm(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let (a, b) = (self.to_integer("lcm")?, other.to_integer("lcm")?);
+        let res = if a.is_zero() || b.is_zero() {
+            BigInt::zero()
+        } else {
+            &a.div_rem(&a.gcd(&b)).unwrap().0.abs() * &b.abs()
+        };
+        return EsianolopValue::from_integer(&[self, other], res, overflow, "lcm");
+    }
+
+    // Valeur absolue (seul i64::MIN déborde)
+    pub fn abs(&self, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        match self {
+            EsianolopValue::Int(a) => overflow.fit_i64((*a as i128).abs(), "absolute value"),
+            EsianolopValue::Big(a) => Ok(overflow.reduce(EsianolopValue::Big(a.abs()))),
+            EsianolopValue::Rational(a) if a.is_negative() => Ok(EsianolopValue::Rational(Rational::from(BigInt::zero()).sub(a))),
+            EsianolopValue::Float(a) => Ok(EsianolopValue::Float(a.abs())),
+            _ => Ok(self.clone()),
+        }
+    }
+
+    // Le plus petit / le plus grand des deux, tel quel (sans changer son type)
+    pub fn min(&self, other: &EsianolopValue) -> EsianolopValue {
+        if other.compare(self) == Ordering::Less { other.clone() } else { self.clone() }
+    }
+
+    pub fn max(&self, other: &EsianolopValue) -> EsianolopValue {
+        if other.compare(self) == Ordering::Greater { other.clone() } else { self.clone() }
+    }
+
+    // Factorielle. En modulo N, on réduit à chaque étape (et le résultat est nul dès que n >= N)
+    pub fn fact(&self, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let n = self.to_integer("factorial")?;
+        if n.is_negative() {
+            return Err(EsianolopErrorKind::Negative("factorial"));
+        }
+        let n = n.to_i64().ok_or(EsianolopErrorKind::Overflow("factorial"))?;
+        let res = match overflow {
+            // n! est un multiple de m dès que n >= m
+            OverflowPolicy::Modular(m) if n >= m => BigInt::zero(),
+            _ if n > MAX_FACTORIAL => return Err(EsianolopErrorKind::InvalidOperand("too big factorial (at most 10000!)")),
+            OverflowPolicy::Modular(m) => {
+                let m = m as i128;
+                BigInt::from((2..=n).fold(1 % m, |acc, i| acc * i as i128 % m) as i64)
+            },
+            // En i64, on s'arrete dès que le résultat ne bouge plus (0 en wrapping à partir de 66!, i64::MAX en saturating)
+            _ if matches!(self, EsianolopValue::Int(_)) => {
+                let mut res = 1i64;
+                for i in 2..=n {
+                    res = match overflow {
+                        OverflowPolicy::Checked => res.checked_mul(i).ok_or(EsianolopErrorKind::Overflow("factorial"))?,
+                        OverflowPolicy::Wrapping => res.wrapping_mul(i),
+                        _ => res.saturating_mul(i),
+                    };
+                    if res == 0 || res == i64::MAX {
+                        break;
+                    }
+                }
+                return Ok(EsianolopValue::Int(res));
+            },
+            _ => (2..=n).fold(BigInt::from(1), |acc, i| &acc * &BigInt::from(i)),
+        };
+        return EsianolopValue::from_integer(&[self], res, overflow, "factorial");
+    }
+
+    // Coefficient binomial "n k choose" : le nombre de façons de choisir k éléments parmi n (0 si k < 0 ou k > n)
+    pub fn choose(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let (n, k) = (self.to_integer("binomial")?, other.to_integer("binomial")?);
+        if n.is_negative() {
+            return Err(EsianolopErrorKind::Negative("binomial"));
+        }
+        let res = if k.is_negative() || k > n {
+            BigInt::zero()
+        } else {
+            // On prend le plus petit de k et n-k, et on multiplie / divise au fur et à mesure (chaque étape est entière)
+            let k = std::cmp::min(k.clone(), &n - &k).to_i64().ok_or(EsianolopErrorKind::Overflow("binomial"))?;
+            if k > MAX_FACTORIAL {
+                return Err(EsianolopErrorKind::InvalidOperand("too big binomial (at most 10000 factors)"));
+            }
+            let mut res = BigInt::from(1);
+            for i in 0..k {
+                res = (&res * &(&n - &BigInt::from(i))).div_rem(&BigInt::from(i + 1)).unwrap().0;
+            }
+            res
+        };
+        return EsianolopValue::from_integer(&[self, other], res, overflow, "binomial");
+    }
+
+    // 1 si la valeur est un nombre premier, 0 sinon (Miller-Rabin)
+    pub fn is_prime(&self) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let n = self.to_integer("isprime")?;
+        if n.bits() > MAX_PRIME_BITS {
+            return Err(EsianolopErrorKind::InvalidOperand("too big number in isprime (more than 512 bits)"));
+        }
+        return Ok(EsianolopValue::from_bool(n.is_probable_prime()));
+    }
+
+    // Le plus petit nombre premier strictement plus grand que la valeur.
+    // La politique de débordement ne s'applique pas : un premier tronqué ou pris modulo N n'en serait plus un.
+    pub fn next_prime(&self) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let mut n = std::cmp::max(self.to_integer("nextprime")?, BigInt::from(1));
+        if n.bits() > MAX_PRIME_BITS {
+            return Err(EsianolopErrorKind::InvalidOperand("too big number in nextprime (more than 512 bits)"));
+        }
+        let one = BigInt::from(1);
+        loop {
+            n = &n + &one;
+            if n.is_probable_prime() {
+                return EsianolopValue::from_integer(&[self], n, OverflowPolicy::Checked, "nextprime");
+            }
+        }
+    }
+
+    // self^exp modulo m, sans jamais calculer la puissance entière. Le résultat est entre 0 et m-1.
+    pub fn modpow(&self, exp: &EsianolopValue, modulus: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let (a, e, m) = (self.to_integer("modpow")?, exp.to_integer("modpow")?, modulus.to_integer("modpow")?);
+        if m.is_zero() {
+            return Err(EsianolopErrorKind::DivisionByZero);
+        }
+        if m.is_negative() {
+            return Err(EsianolopErrorKind::Negative("modulus in modpow"));
+        }
+        let res = a.modpow(&e, &m).ok_or(EsianolopErrorKind::Negative("exponent in modpow"))?;
+        return EsianolopValue::from_integer(&[self, modulus], res, overflow, "modpow");
+    }
+
+    // ----- Opérations sur les bits -----
+    // Les entiers sont vus en complément à deux : sur 64 bits en mode int, et comme si un négatif avait une infinité de 1 devant sinon
+
+    fn bitwise(&self, other: &EsianolopValue, overflow: OverflowPolicy, what: &'static str,
+               int_op: fn(i64, i64) -> i64, digit_op: fn(u32, u32) -> u32) -> Result<EsianolopValue, EsianolopErrorKind> {
+        match (self, other) {
+            (EsianolopValue::Int(a), EsianolopValue::Int(b)) => Ok(overflow.reduce(EsianolopValue::Int(int_op(*a, *b)))),
+            _ => {
+                let res = self.to_integer(what)?.bitwise(&other.to_integer(what)?, digit_op);
+                EsianolopValue::from_integer(&[self, other], res, overflow, what)
+            },
+        }
+    }
+
+    pub fn band(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        self.bitwise(other, overflow, "bitwise and", |a, b| a & b, |a, b| a & b)
+    }
+
+    pub fn bor(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        self.bitwise(other, overflow, "bitwise or", |a, b| a | b, |a, b| a | b)
+    }
+
+    pub fn bxor(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        self.bitwise(other, overflow, "bitwise xor", |a, b| a ^ b, |a, b| a ^ b)
+    }
+
+    // Inverse tous les bits : ça vaut toujours -x-1
+    pub fn bnot(&self, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        if let EsianolopValue::Int(a) = self {
+            return Ok(overflow.reduce(EsianolopValue::Int(!a)));
+        }
+        let res = &(-&self.to_integer("bitwise not")?) - &BigInt::from(1);
+        return EsianolopValue::from_integer(&[self], res, overflow, "bitwise not");
+    }
+
+    // Le décalage (en nombre de bits) : un entier positif
+    fn shift_amount(&self, what: &'static str) -> Result<u64, EsianolopErrorKind> {
+        let shift = self.to_integer(what)?;
+        if shift.is_negative() {
+            return Err(EsianolopErrorKind::Negative("shift amount"));
+        }
+        return shift.to_i64().map(|x| x as u64).ok_or(EsianolopErrorKind::InvalidOperand("too big shift amount"));
+    }
+
+    // Décalage à gauche : multiplie par 2^shift. En mode int, un dépassement suit la politique de débordement.
+    pub fn shl(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let shift = other.shift_amount("left shift")?;
+        let a = match self {
+            EsianolopValue::Int(a) => *a,
+            _ => {
+                // Comme pour la puissance, le résultat est limité à MAX_BITS bits (sauf pour 0)
+                let a = self.to_integer("left shift")?;
+                if !a.is_zero() {
+                    check_bits(a.bits().saturating_add(shift) as f64, "too big result in left shift (more than 100000 bits)")?;
+                }
+                let res = a.shl(shift as u32);
+                return EsianolopValue::from_integer(&[self, other], res, overflow, "left shift");
+            },
+        };
+        // Jusqu'à 63 bits, le résultat exact tient dans un i128
+        if shift < 64 {
+            return overflow.fit_i64((a as i128) << shift, "left shift");
+        }
+        // Au delà, tous les bits sortent des 64 bits (sauf pour 0)
+        let res = match overflow {
+            _ if a == 0 => 0,
+            OverflowPolicy::Checked => return Err(EsianolopErrorKind::Overflow("left shift")),
+            OverflowPolicy::Wrapping => 0,
+            OverflowPolicy::Saturating => if a < 0 { i64::MIN } else { i64::MAX },
+            // a * 2^shift modulo N, sans calculer 2^shift
+            OverflowPolicy::Modular(n) => {
+                let n = BigInt::from(n);
+                let power = BigInt::from(2).modpow(&BigInt::from(shift as i64), &n).unwrap();
+                return Ok(overflow.reduce(EsianolopValue::Big(&BigInt::from(a) * &power)).to_i64().map(EsianolopValue::Int).unwrap());
+            },
+        };
+        return Ok(EsianolopValue::Int(res));
+    }
+
+    // Décalage à droite arithmétique : divise par 2^shift en arrondissant en dessous (ne déborde jamais)
+    pub fn shr(&self, other: &EsianolopValue, overflow: OverflowPolicy) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let shift = other.shift_amount("right shift")?;
+        if let EsianolopValue::Int(a) = self {
+            return Ok(overflow.reduce(EsianolopValue::Int(a >> shift.min(63))));
+        }
+        let res = self.to_integer("right shift")?.shr(shift);
+        return EsianolopValue::from_integer(&[self, other], res, overflow, "right shift");
+    }
+
+    // Le nombre de bits à 1. En mode int, un négatif est compté sur ses 64 bits ; sinon il en a une infinité, c'est une erreur.
+    pub fn popcount(&self) -> Result<EsianolopValue, EsianolopErrorKind> {
+        if let EsianolopValue::Int(a) = self {
+            return Ok(EsianolopValue::Int(a.count_ones() as i64));
+        }
+        let a = self.to_integer("popcount")?;
+        if a.is_negative() {
+            return Err(EsianolopErrorKind::Negative("popcount"));
+        }
+        return EsianolopValue::from_integer(&[self], BigInt::from(a.count_ones() as i64), OverflowPolicy::Checked, "popcount");
+    }
+
+    pub fn sqrt(&self) -> Result<EsianolopValue, EsianolopErrorKind> {
+        if self.is_negative() {
+            return Err(EsianolopErrorKind::Negative("square-root"));
+        }
+        match self {
+            EsianolopValue::Int(a) => Ok(EsianolopValue::Int((*a as f64).sqrt() as i64)),
+            EsianolopValue::Big(a) => a.sqrt().map(EsianolopValue::Big).ok_or(EsianolopErrorKind::Negative("square-root")),
+            // On reste exact : la racine d'une fraction n'existe que si c'est un carré parfait
+            EsianolopValue::Rational(a) => a.sqrt().map(EsianolopValue::Rational).ok_or(EsianolopErrorKind::InvalidOperand("irrational square-root")),
+            EsianolopValue::Float(a) => finite(a.sqrt(), "square-root"),
+        }
+    }
+
+    // Applique une fonction flottante (log, sin...). Les entiers sont tronqués comme pour la racine,
+    // les fractions n'ayant pas de valeur exacte, elles donnent un flottant.
+    // "what" est le nom de la fonction, pour l'erreur si le résultat n'est pas un nombre fini
+    pub fn apply_f64(&self, function: fn(f64) -> f64, what: &'static str) -> Result<EsianolopValue, EsianolopErrorKind> {
+        let res = function(self.to_f64());
+        if !res.is_finite() {
+            return Err(EsianolopErrorKind::NotFinite(what));
+        }
+        match self {
+            EsianolopValue::Int(_) if res.abs() < 9.0e18 => Ok(EsianolopValue::Int(res as i64)),
+            EsianolopValue::Int(_) => Err(EsianolopErrorKind::Overflow(what)),
+            EsianolopValue::Big(_) => Ok(EsianolopValue::Big(BigInt::from_f64(res).unwrap())),
+            _ => Ok(EsianolopValue::Float(res)),
+        }
+    }
+
+    // Les arrondis : les entiers ne changent pas, les fractions restent exactes
+    pub fn floor(&self) -> EsianolopValue {
+        match self {
+            EsianolopValue::Rational(a) => EsianolopValue::Rational(Rational::from(a.floor())),
+            EsianolopValue::Float(a) => EsianolopValue::Float(a.floor()),
+            _ => self.clone(),
+        }
+    }
+
+    pub fn ceil(&self) -> EsianolopValue {
+        match self {
+            EsianolopValue::Rational(a) => EsianolopValue::Rational(Rational::from(a.ceil())),
+            EsianolopValue::Float(a) => EsianolopValue::Float(a.ceil()),
+            _ => self.clone(),
+        }
+    }
+
+    pub fn round(&self) -> EsianolopValue {
+        match self {
+            EsianolopValue::Rational(a) => EsianolopValue::Rational(Rational::from(a.round())),
+            EsianolopValue::Float(a) => EsianolopValue::Float(a.round()),
+            _ => self.clone(),
+        }
+    }
+}
+
+impl fmt::Display for EsianolopValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EsianolopValue::Int(a) => write!(f, "{}", a),
+            EsianolopValue::Big(a) => write!(f, "{}", a),
+            EsianolopValue::Rational(a) => write!(f, "{}", a),
+            EsianolopValue::Float(a) => write!(f, "{:?}", a), // "{:?}" garde le ".0" et passe en notation scientifique pour les grands nombres
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(text: &str) -> Option<EsianolopValue> {
+        EsianolopMode::Int.parse_number(text)
+    }
+
+    fn big(text: &str) -> BigInt {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_radix_literals() {
+        assert_eq!(int("0x1f"), Some(EsianolopValue::Int(31)));
+        assert_eq!(int("-0xff"), Some(EsianolopValue::Int(-255)));
+        assert_eq!(int("0b1010"), Some(EsianolopValue::Int(10)));
+        assert_eq!(int("+0o17"), Some(EsianolopValue::Int(15)));
+        assert_eq!(int("0x"), None);
+        assert_eq!(int("0b102"), None);
+        assert_eq!(int("0o8"), None);
+        // Trop grand pour un i64, mais pas pour un BigInt
+        assert_eq!(int("0x10000000000000000"), None);
+        assert_eq!(EsianolopMode::Big.parse_number("0x10000000000000000"), Some(EsianolopValue::Big(big("18446744073709551616"))));
+        assert_eq!(EsianolopMode::Rational.parse_number("0b11"), Some(EsianolopValue::Rational(Rational::from(big("3")))));
+        assert_eq!(EsianolopMode::Float.parse_number("0x10"), Some(EsianolopValue::Float(16.0)));
+    }
+
+    #[test]
+    fn parse_underscores() {
+        assert_eq!(int("1_000_000"), Some(EsianolopValue::Int(1_000_000)));
+        assert_eq!(int("0xff_ff"), Some(EsianolopValue::Int(0xffff)));
+        assert_eq!(int("0b1_0"), Some(EsianolopValue::Int(2)));
+        assert_eq!(int("1e1_0"), Some(EsianolopValue::Int(10_000_000_000)));
+        for text in &["_1", "1_", "1__0", "0x_1f", "0b_1", "0b1_2", "1_e6", "1e_6", "1_.5", "1._5"] {
+            assert_eq!(EsianolopMode::Rational.parse_number(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_scientific() {
+        assert_eq!(int("1e6"), Some(EsianolopValue::Int(1_000_000)));
+        assert_eq!(int("2.5e3"), Some(EsianolopValue::Int(2500)));
+        // Un entier doit etre exact
+        assert_eq!(int("1.5e-3"), None);
+        assert_eq!(int("1e19"), None);
+        assert_eq!(EsianolopMode::Big.parse_number("1e19"), Some(EsianolopValue::Big(big("10000000000000000000"))));
+        assert_eq!(EsianolopMode::Rational.parse_number("1.5e-3"), Some(EsianolopValue::Rational(Rational::new(big("3"), big("2000")).unwrap())));
+        assert_eq!(EsianolopMode::Float.parse_number("1.5e-3"), Some(EsianolopValue::Float(0.0015)));
+        assert_eq!(EsianolopMode::Float.parse_number("1e400"), None);
+        assert_eq!(EsianolopMode::Float.parse_number("inf"), None);
+    }
+
+    #[test]
+    fn overflow_policy_names() {
+        assert_eq!(OverflowPolicy::from_name("wrap"), Some(OverflowPolicy::Wrapping));
+        assert_eq!(OverflowPolicy::from_name(" mod 7"), Some(OverflowPolicy::Modular(7)));
+        assert_eq!(OverflowPolicy::from_name("mod:1000000007"), Some(OverflowPolicy::Modular(1_000_000_007)));
+        assert_eq!(OverflowPolicy::from_name("mod 0"), None);
+        assert_eq!(OverflowPolicy::from_name("checked now"), None);
+    }
+
+    #[test]
+    fn fit_i64_each_policy() {
+        let max = i64::MAX as i128;
+        let fit = |policy: OverflowPolicy, exact: i128| policy.fit_i64(exact, "addition");
+        assert_eq!(fit(OverflowPolicy::Checked, 42), Ok(EsianolopValue::Int(42)));
+        assert_eq!(fit(OverflowPolicy::Checked, max + 1), Err(EsianolopErrorKind::Overflow("addition")));
+        assert_eq!(fit(OverflowPolicy::Wrapping, max + 1), Ok(EsianolopValue::Int(i64::MIN)));
+        assert_eq!(fit(OverflowPolicy::Wrapping, -max - 2), Ok(EsianolopValue::Int(i64::MAX)));
+        assert_eq!(fit(OverflowPolicy::Saturating, max * 3), Ok(EsianolopValue::Int(i64::MAX)));
+        assert_eq!(fit(OverflowPolicy::Saturating, -max * 3), Ok(EsianolopValue::Int(i64::MIN)));
+        // Le modulo donne toujours un résultat entre 0 et N-1
+        assert_eq!(fit(OverflowPolicy::Modular(7), max + 1), Ok(EsianolopValue::Int(((max + 1) % 7) as i64)));
+        assert_eq!(fit(OverflowPolicy::Modular(7), -3), Ok(EsianolopValue::Int(4)));
+    }
+
+    #[test]
+    fn reduce_each_policy() {
+        for policy in &[OverflowPolicy::Checked, OverflowPolicy::Wrapping, OverflowPolicy::Saturating] {
+            assert_eq!(policy.reduce(EsianolopValue::Int(-10)), EsianolopValue::Int(-10));
+            assert_eq!(policy.reduce(EsianolopValue::Big(big("-100000000000000000000"))), EsianolopValue::Big(big("-100000000000000000000")));
+        }
+        let modular = OverflowPolicy::Modular(1_000_000_007);
+        assert_eq!(modular.reduce(EsianolopValue::Int(-1)), EsianolopValue::Int(1_000_000_006));
+        assert_eq!(modular.reduce(EsianolopValue::Big(big("100000000000000000000"))), EsianolopValue::Big(big("4900")));
+        assert_eq!(modular.reduce(EsianolopValue::Big(big("-100000000000000000000"))), EsianolopValue::Big(big("999995107")));
+        // Les fractions et les flottants ne sont pas touchés
+        assert_eq!(modular.reduce(EsianolopValue::Float(-2.5)), EsianolopValue::Float(-2.5));
+    }
+}